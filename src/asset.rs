@@ -1,26 +1,226 @@
+use std::path::{Path, PathBuf};
+
+use super::error::asset::{AssetError, PathError};
+
 pub const DATA_PATH: &str = "assets";
 
+pub fn normalize<S>(path: S) -> Result<PathBuf, PathError>
+    where
+        S: AsRef<str>,
+{
+    let path = path.as_ref();
+
+    if path.starts_with('/') || path.starts_with('\\') || has_drive_prefix(path) {
+        return Err(PathError::Absolute);
+    }
+
+    let mut res = PathBuf::new();
+
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return Err(PathError::Escape),
+            _ => res.push(part),
+        }
+    }
+
+    if res.as_os_str().is_empty() {
+        return Err(PathError::Empty);
+    }
+
+    Ok(res)
+}
+
+// Windows drive like `C:` or `c:/`, other colons are part of the name
+fn has_drive_prefix(path: &str) -> bool {
+    let mut chars = path.chars();
+
+    matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some(d), Some(':'), None | Some('/') | Some('\\')) if d.is_ascii_alphabetic()
+    )
+}
+
+#[derive(Clone, Debug)]
+pub struct AssetRoot {
+    roots: Vec<PathBuf>,
+}
+
+impl AssetRoot {
+    pub fn new<P>(base: P) -> Self
+        where
+            P: Into<PathBuf>,
+    { AssetRoot { roots: vec![base.into()] } }
+
+    pub fn push<P>(&mut self, root: P) -> &mut Self
+        where
+            P: Into<PathBuf>,
+    {
+        self.roots.push(root.into());
+        self
+    }
+
+    pub fn with<P>(mut self, root: P) -> Self
+        where
+            P: Into<PathBuf>,
+    {
+        self.push(root);
+        self
+    }
+
+    pub fn roots(&self) -> &[PathBuf] { &self.roots }
+
+    pub fn resolve<S>(&self, dir: &str, file: S) -> Result<PathBuf, PathError>
+        where
+            S: AsRef<str>,
+    {
+        let relative = Path::new(dir).join(normalize(file.as_ref())?);
+
+        self.roots
+            .iter()
+            .rev()
+            .map(|root| root.join(&relative))
+            .find(|path| path.is_file())
+            .ok_or_else(|| PathError::NotFound(relative.to_string_lossy().into_owned()))
+    }
+
+    pub fn resolve_asset<T, S>(&self, file: S) -> Result<String, PathError>
+        where
+            T: Asset,
+            S: AsRef<str>,
+    {
+        self.resolve(T::DIR, file)
+            .map(|path| path.to_string_lossy().into_owned())
+    }
+
+    // Like `resolve_asset`, but a file missing from every root maps into the base root,
+    // so loading it reports the loader's own error
+    pub fn locate<T, S>(&self, file: S) -> Result<String, PathError>
+        where
+            T: Asset,
+            S: AsRef<str>,
+    {
+        match self.resolve_asset::<T, _>(file) {
+            Err(PathError::NotFound(relative)) => Ok(self.roots[0].join(relative).to_string_lossy().into_owned()),
+            res => res,
+        }
+    }
+}
+
+impl Default for AssetRoot {
+    fn default() -> Self { AssetRoot::new(DATA_PATH) }
+}
+
 pub trait Asset: super::load::Load {
     const DIR: &'static str;
 
-    fn full_path<S>(file: S) -> String
+    fn full_path<S>(file: S) -> Result<String, PathError>
+        where
+            S: AsRef<str>,
+    { AssetRoot::default().locate::<Self, _>(file) }
+
+    fn load_asset_from<S>(root: &AssetRoot, file: S, loader: &mut Self::Loader)
+                          -> Result<Self, AssetError<Self::Error>>
         where
             S: AsRef<str>,
     {
-        let mut s = String::from(DATA_PATH);
-        s.push(std::path::MAIN_SEPARATOR);
-        s.push_str(Self::DIR);
-        s.push(std::path::MAIN_SEPARATOR);
-        s.push_str(file.as_ref());
-        s
+        let path = root.locate::<Self, _>(file)?;
+        Self::load(path, loader).map_err(AssetError::LoadError)
     }
 
-    fn load_asset<S>(file: S, loader: &mut Self::Loader) -> Result<Self, Self::Error>
+    fn load_asset<S>(file: S, loader: &mut Self::Loader) -> Result<Self, AssetError<Self::Error>>
         where
             S: AsRef<str>,
-    { Self::load(Self::full_path(file), loader) }
+    { Self::load_asset_from(&AssetRoot::default(), file, loader) }
 }
 
 impl<T: Asset> Asset for std::rc::Rc<T> { const DIR: &'static str = T::DIR; }
 
 impl<T: Asset> Asset for Box<T> { const DIR: &'static str = T::DIR; }
+
+impl<T: Asset> Asset for std::sync::Arc<T> { const DIR: &'static str = T::DIR; }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn normalize_path() {
+        assert_eq!(normalize("a/b.yml"), Ok(PathBuf::from("a").join("b.yml")));
+        assert_eq!(normalize("./a//b.yml"), Ok(PathBuf::from("a").join("b.yml")));
+        assert_eq!(normalize("a\\b.yml"), Ok(PathBuf::from("a").join("b.yml")));
+        assert_eq!(normalize("a/../b.yml"), Err(PathError::Escape));
+        assert_eq!(normalize("../b.yml"), Err(PathError::Escape));
+        assert_eq!(normalize("/etc/passwd"), Err(PathError::Absolute));
+        assert_eq!(normalize("c:/b.yml"), Err(PathError::Absolute));
+        assert_eq!(normalize("C:"), Err(PathError::Absolute));
+        assert_eq!(normalize("C:\\b.yml"), Err(PathError::Absolute));
+        assert_eq!(normalize("a:b.yml"), Ok(PathBuf::from("a:b.yml")));
+        assert_eq!(normalize("dir/ab:c"), Ok(PathBuf::from("dir").join("ab:c")));
+        assert_eq!(normalize("./"), Err(PathError::Empty));
+    }
+
+    #[derive(Debug)]
+    struct Text(String);
+
+    impl crate::load::Load for Text {
+        type Error = std::io::Error;
+        type Loader = ();
+
+        fn load<S>(file: S, _: &mut Self::Loader) -> Result<Self, Self::Error>
+            where
+                S: AsRef<str>,
+        { fs::read_to_string(file.as_ref()).map(Text) }
+    }
+
+    impl Asset for Text {
+        const DIR: &'static str = "texts";
+    }
+
+    #[test]
+    fn resolve() {
+        let dir = std::env::temp_dir().join(format!("rt_tools_root_{}", std::process::id()));
+        let base = dir.join("base");
+        let mod_a = dir.join("mod_a");
+        let mod_b = dir.join("mod_b");
+
+        for root in &[&base, &mod_a, &mod_b] {
+            fs::create_dir_all(root.join("texts")).unwrap();
+        }
+
+        fs::write(base.join("texts").join("one"), "base one").unwrap();
+        fs::write(base.join("texts").join("two"), "base two").unwrap();
+        fs::write(mod_a.join("texts").join("two"), "a two").unwrap();
+        fs::write(mod_b.join("texts").join("two"), "b two").unwrap();
+
+        let root = AssetRoot::new(&base)
+            .with(&mod_a)
+            .with(&mod_b);
+
+        assert_eq!(Text::load_asset_from(&root, "one", &mut ()).unwrap().0, "base one");
+        assert_eq!(Text::load_asset_from(&root, "./two", &mut ()).unwrap().0, "b two");
+
+        let err = Text::load_asset_from(&root, "../texts/one", &mut ()).unwrap_err();
+        assert!(matches!(err, AssetError::PathError(PathError::Escape)));
+
+        let err = Text::load_asset_from(&root, "three", &mut ()).unwrap_err();
+        assert!(matches!(err, AssetError::LoadError(_)));
+        assert!(matches!(root.resolve_asset::<Text, _>("three"), Err(PathError::NotFound(_))));
+
+        let two = mod_b.join("texts").join("two").to_string_lossy().into_owned();
+        let three = base.join("texts").join("three").to_string_lossy().into_owned();
+        assert_eq!(root.locate::<Text, _>("two"), Ok(two));
+        assert_eq!(root.locate::<Text, _>("three"), Ok(three));
+        assert_eq!(root.locate::<Text, _>("../two"), Err(PathError::Escape));
+        assert_eq!(Text::full_path("three"), Ok(Path::new(DATA_PATH).join("texts").join("three").to_string_lossy().into_owned()));
+
+        let mut texts = crate::engine::resource::Resource::<Text>::with_root(root.clone());
+        let (two, _) = texts.load_asset("two").unwrap();
+        assert_eq!(texts.get(two).unwrap().0, "b two");
+        assert!(matches!(texts.load_asset("three"), Err(AssetError::LoadError(_))));
+        assert!(matches!(texts.load_asset("/three"), Err(AssetError::PathError(PathError::Absolute))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
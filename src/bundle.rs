@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::{
+    asset::AssetRoot,
+    engine::{
+        atlas::{Atlas, AtlasError, TexturePath},
+        dependency::{DependencyGraph, Node},
+        handle::Handle,
+        model::Symmetry,
+        resource::Resource,
+        sprite_map::SpriteMap,
+        tile::Tile,
+    },
+    error::{asset::AssetError, bundle::BundleError},
+    load::{Load, ReadError},
+};
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Manifest {
+    pub sprite_size: u32,
+    #[serde(default)]
+    pub tiles: Vec<String>,
+}
+
+impl Load for Manifest {
+    type Error = ReadError<serde_yaml::Error>;
+    type Loader = ();
+
+    fn load<S>(file: S, _: &mut Self::Loader) -> Result<Self, Self::Error>
+        where
+            S: AsRef<str>,
+    {
+        let code = std::fs::read_to_string(file.as_ref())?;
+        serde_yaml::from_str(&code).map_err(ReadError::LoadError)
+    }
+}
+
+pub type TileLoadError<M> = AssetError<<Tile<M, TexturePath> as Load>::Error>;
+
+type Sprites = (SpriteMap, HashMap<Handle<TexturePath>, u32>);
+
+#[derive(Debug)]
+pub struct Reload<M>
+    where
+        M: Load,
+{
+    pub tiles: Vec<usize>,
+    pub models: Vec<(Handle<M>, M::Error)>,
+    pub textures: Vec<(Handle<TexturePath>, AtlasError)>,
+}
+
+pub struct Bundle<M>
+    where
+        M: Load<Loader=()> + Symmetry,
+{
+    pub tiles: Resource<Tile<M, TexturePath>>,
+    pub models: Resource<M>,
+    pub textures: Resource<TexturePath>,
+    pub sprites: SpriteMap,
+    pub dependencies: DependencyGraph<M, TexturePath>,
+    pub failed: Vec<(String, BundleError<TileLoadError<M>>)>,
+    sprites_of: HashMap<Handle<TexturePath>, u32>,
+    sprite_size: u32,
+}
+
+impl<M> Bundle<M>
+    where
+        M: Load<Loader=()> + Symmetry,
+{
+    pub fn load(manifest: &Manifest) -> Result<Self, AtlasError> {
+        Self::load_in(manifest, AssetRoot::default())
+    }
+
+    pub fn load_in(manifest: &Manifest, root: AssetRoot) -> Result<Self, AtlasError> {
+        Self::load_with(manifest, root, image::open)
+    }
+
+    // Tiles of the manifest are assets under `root`. A tile whose textures can't be opened
+    // fails on its own, its models and textures are released and evicted when no other tile uses them
+    pub fn load_with<F>(manifest: &Manifest, root: AssetRoot, mut open: F) -> Result<Self, AtlasError>
+        where
+            F: FnMut(String) -> image::ImageResult<image::DynamicImage>,
+    {
+        let mut loaders = (Resource::new(), Resource::new());
+        let mut tiles: Resource<Tile<M, TexturePath>> = Resource::with_root(root);
+        let mut dependencies = DependencyGraph::new();
+        let mut images = HashMap::new();
+        let mut failed = Vec::new();
+
+        for file in &manifest.tiles {
+            let handle = match tiles.load_asset_with(file, &mut loaders) {
+                Ok((handle, _)) => handle,
+                Err(e) => {
+                    failed.push((file.clone(), BundleError::TileError(e)));
+                    continue;
+                }
+            };
+
+            let (models, textures) = &mut loaders;
+
+            let opened = match tiles.get(handle) {
+                Some(tile) => open_textures(tile, textures, manifest.sprite_size, &mut images, &mut open),
+                None => continue,
+            };
+
+            match opened {
+                Ok(()) => {
+                    if let Some(tile) = tiles.get_mut(handle) {
+                        tile.id = handle.index() as u32;
+                        dependencies.add_tile(handle.index(), tile);
+                    }
+                }
+                Err((texture, e)) => {
+                    if let Some(tile) = tiles.unload(handle) {
+                        tile.release(models, textures);
+                    }
+
+                    failed.push((file.clone(), BundleError::TextureError(texture, e)));
+                }
+            }
+        }
+
+        let (mut models, mut textures) = loaders;
+        models.evict_unused();
+        textures.evict_unused();
+
+        let (sprites, sprites_of) = stitch(&textures, manifest.sprite_size, images, &mut open)?;
+
+        Ok(Bundle {
+            tiles,
+            models,
+            textures,
+            sprites,
+            dependencies,
+            failed,
+            sprites_of,
+            sprite_size: manifest.sprite_size,
+        })
+    }
+
+    // Position of the texture in `sprites`, texture indexes may have gaps after failed tiles
+    pub fn sprite(&self, texture: Handle<TexturePath>) -> Option<u32> { self.sprites_of.get(&texture).copied() }
+
+    pub fn reload_changed(&mut self) -> Result<Reload<M>, AtlasError> { self.reload_changed_with(image::open) }
+
+    // Reloads changed models and textures and returns the tiles that use them. The sprites are
+    // stitched again once a texture changed, if any texture can't be opened the previous sprites stay
+    pub fn reload_changed_with<F>(&mut self, mut open: F) -> Result<Reload<M>, AtlasError>
+        where
+            F: FnMut(String) -> image::ImageResult<image::DynamicImage>,
+    {
+        let models = self.models.reload_changed();
+        let textures = self.textures.reload_changed();
+        let mut failed = Vec::new();
+
+        if !textures.changed.is_empty() {
+            let mut images = HashMap::new();
+
+            for (handle, TexturePath(file)) in self.textures.entries() {
+                match open_texture(file, self.sprite_size, &mut open) {
+                    Ok(img) => {
+                        images.insert(file.clone(), img);
+                    }
+                    Err(e) => failed.push((handle, e)),
+                }
+            }
+
+            if failed.is_empty() {
+                let (sprites, sprites_of) = stitch(&self.textures, self.sprite_size, images, &mut open)?;
+                self.sprites = sprites;
+                self.sprites_of = sprites_of;
+            }
+        }
+
+        let changed = models.changed
+            .into_iter()
+            .map(Node::Model)
+            .chain(textures.changed.into_iter().map(Node::Texture));
+
+        Ok(Reload {
+            tiles: self.dependencies.invalid_tiles(changed),
+            models: models.failed,
+            textures: failed,
+        })
+    }
+
+    pub fn is_complete(&self) -> bool { self.failed.is_empty() }
+}
+
+fn open_textures<M, F>(
+    tile: &Tile<M, TexturePath>,
+    textures: &Resource<TexturePath>,
+    size: u32,
+    images: &mut HashMap<String, image::DynamicImage>,
+    open: &mut F,
+) -> Result<(), (String, AtlasError)>
+    where
+        F: FnMut(String) -> image::ImageResult<image::DynamicImage>,
+{
+    for texture in tile.textures() {
+        let file = match textures.get(texture) {
+            Some(TexturePath(file)) => file,
+            None => continue,
+        };
+
+        if images.contains_key(file) {
+            continue;
+        }
+
+        let img = open_texture(file, size, open).map_err(|e| (file.clone(), e))?;
+        images.insert(file.clone(), img);
+    }
+
+    Ok(())
+}
+
+fn open_texture<F>(file: &str, size: u32, open: &mut F) -> Result<image::DynamicImage, AtlasError>
+    where
+        F: FnMut(String) -> image::ImageResult<image::DynamicImage>,
+{
+    use image::GenericImageView;
+
+    let img = open(file.to_owned())?;
+
+    if img.width() != size || img.height() != size {
+        return Err(AtlasError::IncorrectSpriteSize);
+    }
+
+    Ok(img)
+}
+
+// Images already opened are taken from `images`, the rest are opened
+fn stitch<F>(
+    textures: &Resource<TexturePath>,
+    size: u32,
+    mut images: HashMap<String, image::DynamicImage>,
+    open: &mut F,
+) -> Result<Sprites, AtlasError>
+    where
+        F: FnMut(String) -> image::ImageResult<image::DynamicImage>,
+{
+    let mut atlas = Atlas::new(size);
+    let mut sprites_of = HashMap::new();
+
+    for (handle, TexturePath(file)) in textures.entries() {
+        if let Some(sprite) = atlas.add(file.as_str()) {
+            sprites_of.insert(handle, sprite as u32);
+        }
+    }
+
+    let sprites = atlas.stitch(|file| match images.remove(&file) {
+        Some(img) => Ok(img),
+        None => open(file),
+    })?;
+
+    Ok((sprites, sprites_of))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        engine::model::Model,
+        error::asset::PathError,
+        load::ReadError,
+        parse::ParseError,
+        error::tile::{StateError, TileError},
+    };
+    use std::fs;
+
+    #[test]
+    fn load() {
+        let dir = std::env::temp_dir().join(format!("rt_tools_bundle_{}", std::process::id()));
+        fs::create_dir_all(dir.join("tiles")).unwrap();
+
+        let path = |file: &str| dir.join(file).to_string_lossy().into_owned();
+        let tile_path = |file: &str| dir.join("tiles").join(file).to_string_lossy().into_owned();
+
+        let model = "{ pos: [[0, 0, 0], [1, 0, 0], [0, 1, 0]], st: [[0, 0], [1, 0], [0, 1]], \
+                     faces: [{ data: { pos: [0, 1, 2], st: [0, 1, 2] } }] }";
+        fs::write(dir.join("tri.yml"), model).unwrap();
+        fs::write(dir.join("tri2.yml"), model).unwrap();
+
+        for (i, name) in ["red.png", "blue.png"].iter().enumerate() {
+            let img: image::RgbaImage = image::ImageBuffer::from_pixel(4, 4, image::Rgba([255 * i as u8, 0, 0, 255]));
+            img.save(dir.join(name)).unwrap();
+        }
+
+        let big: image::RgbaImage = image::ImageBuffer::from_pixel(8, 8, image::Rgba([0, 0, 0, 255]));
+        big.save(dir.join("big.png")).unwrap();
+
+        let tile = |model: &str, textures: &[&str]| format!(
+            "{{ models: [ '{}' ], textures: [ {} ], states: [ {{ model: 0, layers: [ {} ] }} ] }}",
+            path(model),
+            textures.iter().map(|t| format!("'{}'", path(t))).collect::<Vec<_>>().join(", "),
+            (0..textures.len()).map(|i| i.to_string()).collect::<Vec<_>>().join(", "),
+        );
+
+        fs::write(tile_path("one.yml"), tile("tri.yml", &["red.png"])).unwrap();
+        fs::write(tile_path("two.yml"), tile("tri.yml", &["blue.png"])).unwrap();
+        fs::write(tile_path("bad.yml"), "{ states: [ { layers: [ 0 ] } ] }").unwrap();
+        fs::write(tile_path("no_texture.yml"), tile("tri2.yml", &["red.png", "missing.png"])).unwrap();
+        fs::write(tile_path("big.yml"), tile("tri.yml", &["big.png"])).unwrap();
+
+        let manifest_code = "{ sprite_size: 4, tiles: [ one.yml, bad.yml, no_texture.yml, two.yml, missing.yml, big.yml, ../one.yml ] }";
+        fs::write(dir.join("manifest.yml"), manifest_code).unwrap();
+
+        let manifest = Manifest::load(path("manifest.yml"), &mut ()).unwrap();
+        assert_eq!(manifest.tiles.len(), 7);
+
+        let bundle: Bundle<Model> = Bundle::load_in(&manifest, AssetRoot::new(&dir)).unwrap();
+
+        assert_eq!(bundle.tiles.len(), 2);
+        assert_eq!(bundle.models.len(), 1);
+        assert_eq!(bundle.textures.len(), 2);
+        assert_eq!(image::GenericImageView::width(bundle.sprites.map()), 8);
+
+        let two = bundle.tiles.get(tile_path("two.yml")).unwrap();
+        assert_eq!(two.id, 1);
+        assert_eq!(bundle.textures.get(two.states[0].layers[0]).unwrap().0, path("blue.png"));
+        assert_eq!(bundle.models.get(two.states[0].model).unwrap().faces.len(), 1);
+
+        let one = bundle.tiles.get(tile_path("one.yml")).unwrap();
+        assert_eq!(bundle.dependencies.tiles_using_model(two.states[0].model), [0, 1]);
+        assert_eq!(bundle.dependencies.tiles_using_texture(one.states[0].layers[0]), [0]);
+        assert_eq!(bundle.models.refs(two.states[0].model), Some(2));
+        assert_eq!(bundle.sprite(one.states[0].layers[0]), Some(0));
+        assert_eq!(bundle.sprite(two.states[0].layers[0]), Some(1));
+        assert!(bundle.tiles.get(tile_path("no_texture.yml")).is_none());
+        assert!(bundle.models.get(path("tri2.yml")).is_none());
+        assert!(bundle.textures.get(path("big.png")).is_none());
+
+        assert!(!bundle.is_complete());
+        assert_eq!(bundle.failed.len(), 5);
+        assert_eq!(bundle.failed[0].0, "bad.yml");
+        assert!(matches!(
+            bundle.failed[0].1,
+            BundleError::TileError(AssetError::LoadError(ReadError::LoadError(ParseError::ConvertError(
+                TileError::StateError(StateError::NoModelDefined)
+            )))),
+        ));
+        assert_eq!(bundle.failed[1].0, "no_texture.yml");
+        assert!(matches!(
+            bundle.failed[1].1,
+            BundleError::TextureError(ref file, AtlasError::ImageError(_)) if *file == path("missing.png"),
+        ));
+        assert_eq!(bundle.failed[2].0, "missing.yml");
+        assert!(matches!(bundle.failed[2].1, BundleError::TileError(AssetError::LoadError(ReadError::IoError(_)))));
+        assert_eq!(bundle.failed[3].0, "big.yml");
+        assert!(matches!(bundle.failed[3].1, BundleError::TextureError(_, AtlasError::IncorrectSpriteSize)));
+        assert_eq!(bundle.failed[4].0, "../one.yml");
+        assert!(matches!(bundle.failed[4].1, BundleError::TileError(AssetError::PathError(PathError::Escape))));
+
+        let blue = two.states[0].layers[0];
+        let mut bundle = bundle;
+        let reload = bundle.reload_changed().unwrap();
+        assert!(reload.tiles.is_empty() && reload.models.is_empty() && reload.textures.is_empty());
+
+        let pixel = |bundle: &Bundle<Model>| image::GenericImageView::get_pixel(bundle.sprites.map(), 4, 0);
+        let touch = |file: &str, secs| {
+            let later = std::time::SystemTime::now() + std::time::Duration::from_secs(secs);
+            fs::File::options().write(true).open(dir.join(file)).unwrap().set_modified(later).unwrap();
+        };
+
+        let green: image::RgbaImage = image::ImageBuffer::from_pixel(4, 4, image::Rgba([0, 255, 0, 255]));
+        green.save(dir.join("blue.png")).unwrap();
+        touch("blue.png", 10);
+        let reload = bundle.reload_changed().unwrap();
+        assert_eq!(reload.tiles, [1]);
+        assert!(reload.textures.is_empty());
+        assert_eq!(pixel(&bundle), image::Rgba([0, 255, 0, 255]));
+        assert_eq!(bundle.sprite(blue), Some(1));
+
+        big.save(dir.join("blue.png")).unwrap();
+        touch("blue.png", 20);
+        let reload = bundle.reload_changed().unwrap();
+        assert_eq!(reload.tiles, [1]);
+        assert!(matches!(reload.textures[..], [(handle, AtlasError::IncorrectSpriteSize)] if handle == blue));
+        assert_eq!(pixel(&bundle), image::Rgba([0, 255, 0, 255]));
+
+        touch("tri.yml", 10);
+        let reload = bundle.reload_changed().unwrap();
+        assert_eq!(reload.tiles, [0, 1]);
+        assert!(reload.models.is_empty() && reload.textures.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
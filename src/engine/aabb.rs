@@ -0,0 +1,129 @@
+use glm::{vec3, Vec3};
+
+use super::{
+    point::Point,
+    region::Region,
+    transform::Transform,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(a: Vec3, b: Vec3) -> Self { Aabb { min: glm::min2(&a, &b), max: glm::max2(&a, &b) } }
+
+    pub fn unit() -> Self { Aabb { min: vec3(0., 0., 0.), max: vec3(1., 1., 1.) } }
+
+    pub fn from_points<I>(points: I) -> Option<Self>
+        where
+            I: IntoIterator<Item=Vec3>,
+    {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Aabb { min: first, max: first }, |res, p| Aabb {
+            min: glm::min2(&res.min, &p),
+            max: glm::max2(&res.max, &p),
+        }))
+    }
+
+    pub fn size(&self) -> Vec3 { self.max - self.min }
+
+    pub fn center(&self) -> Vec3 { (self.min + self.max) * 0.5 }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+
+        [
+            vec3(a.x, a.y, a.z),
+            vec3(b.x, a.y, a.z),
+            vec3(a.x, b.y, a.z),
+            vec3(b.x, b.y, a.z),
+            vec3(a.x, a.y, b.z),
+            vec3(b.x, a.y, b.z),
+            vec3(a.x, b.y, b.z),
+            vec3(b.x, b.y, b.z),
+        ]
+    }
+
+    pub fn edges(&self) -> [(Vec3, Vec3); 12] {
+        let c = self.corners();
+
+        [
+            (c[0], c[1]), (c[2], c[3]), (c[4], c[5]), (c[6], c[7]),
+            (c[0], c[2]), (c[1], c[3]), (c[4], c[6]), (c[5], c[7]),
+            (c[0], c[4]), (c[1], c[5]), (c[2], c[6]), (c[3], c[7]),
+        ]
+    }
+
+    pub fn translate(&self, v: Vec3) -> Self { Aabb { min: self.min + v, max: self.max + v } }
+
+    pub fn transform(&self, transform: &Transform) -> Self {
+        Self::from_points(self.corners().iter().map(|&p| transform.apply_point(p)))
+            .unwrap_or(*self)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Aabb {
+            min: glm::min2(&self.min, &other.min),
+            max: glm::max2(&self.max, &other.max),
+        }
+    }
+
+    // Boxes that only touch don't intersect, so a body resting on the ground is free to slide
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..3).all(|i| self.min[i] < other.max[i] && other.min[i] < self.max[i])
+    }
+
+    pub fn contains(&self, p: Vec3) -> bool { (0..3).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i]) }
+
+    pub fn cells(&self) -> Region {
+        let last = |v: f32, min: f32| (v.ceil() - 1.).max(min.floor()) as i32;
+
+        Region::new(
+            self.min.into(),
+            Point(last(self.max.x, self.min.x), last(self.max.y, self.min.y), last(self.max.z, self.min.z)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        axis::Axis,
+        shell_transform::{Shell, ShellTransform},
+    };
+
+    #[test]
+    fn aabb() {
+        let slab = Aabb::new(vec3(1., 0.5, 1.), vec3(0., 0., 0.));
+        assert_eq!(slab.min, vec3(0., 0., 0.));
+        assert_eq!(slab.size(), vec3(1., 0.5, 1.));
+        assert!(slab.contains(vec3(0.5, 0.5, 0.)));
+        assert!(!slab.contains(vec3(0.5, 0.6, 0.)));
+
+        let mut shell = Shell::new();
+        shell.flip(Axis::Y);
+        assert_eq!(slab.transform(&shell.into()), Aabb::new(vec3(0., 0.5, 0.), vec3(1., 1., 1.)));
+
+        assert!(slab.intersects(&Aabb::unit()));
+        assert!(!slab.intersects(&slab.translate(vec3(0., 0.5, 0.))));
+        assert_eq!(slab.union(&slab.translate(vec3(1., 0., 0.))).size(), vec3(2., 0.5, 1.));
+
+        let points = [vec3(0.2, 1., 3.), vec3(-1., 0.5, 2.), vec3(0., 0., 4.)];
+        assert_eq!(Aabb::from_points(points.iter().copied()), Some(Aabb::new(vec3(-1., 0., 2.), vec3(0.2, 1., 4.))));
+        assert!(Aabb::from_points(vec![]).is_none());
+
+        let edges = slab.edges();
+        assert!(edges.iter().all(|(a, b)| (0..3).filter(|&i| a[i] != b[i]).count() == 1));
+        assert_eq!(edges.iter().map(|(a, b)| glm::distance(a, b)).sum::<f32>(), 10.);
+
+        let body = Aabb::new(vec3(-0.3, 1., 2.7), vec3(0.3, 2.8, 3.));
+        assert_eq!(body.cells(), Region::new(Point(-1, 1, 2), Point(0, 2, 2)));
+        assert_eq!(Aabb::unit().cells(), Region::point(Point(0, 0, 0)));
+    }
+}
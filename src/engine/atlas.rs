@@ -3,7 +3,8 @@ use super::{
     sprite_map::SpriteMap,
     super::{
         asset::Asset,
-        load::Load,
+        load::{Load, LoadBytes},
+        vfs::Vfs,
     },
 };
 
@@ -17,8 +18,8 @@ impl From<image::ImageError> for AtlasError {
     fn from(err: image::ImageError) -> Self { AtlasError::ImageError(err) }
 }
 
-#[derive(Debug)]
-struct TexturePath(String);
+#[derive(Debug, PartialEq)]
+pub struct TexturePath(pub String);
 
 impl Asset for TexturePath {
     const DIR: &'static str = "textures";
@@ -34,6 +35,13 @@ impl Load for TexturePath {
     { Ok(TexturePath(file.as_ref().into())) }
 }
 
+impl LoadBytes for image::DynamicImage {
+    type Error = image::ImageError;
+    type Loader = ();
+
+    fn load_bytes(bytes: &[u8], _: &mut Self::Loader) -> Result<Self, Self::Error> { image::load_from_memory(bytes) }
+}
+
 #[derive(Debug)]
 pub struct Atlas {
     images: Resource<TexturePath>,
@@ -51,10 +59,15 @@ impl Atlas {
     pub fn add<S>(&mut self, file: S) -> Option<usize>
         where
             S: Into<String>,
-    { self.images.load(file).map(|(idx, _)| idx).ok() }
+    { self.images.load(file).map(|(handle, _)| handle.index()).ok() }
 
     pub fn stitch_sprites(self) -> Result<SpriteMap, AtlasError> { self.stitch(image::open) }
 
+    pub fn stitch_from<V>(self, vfs: &V) -> Result<SpriteMap, AtlasError>
+        where
+            V: Vfs + ?Sized,
+    { self.stitch(|file| vfs.open_image(&file)) }
+
     pub fn stitch<F>(self, mut f: F) -> Result<SpriteMap, AtlasError>
         where
             F: FnMut(String) -> image::ImageResult<image::DynamicImage>,
@@ -145,4 +158,41 @@ mod tests {
         assert_eq!(img.width(), SIZE * 2);
         assert_eq!(img.height(), SIZE * 2);
     }
+
+    #[test]
+    fn stitch_from() {
+        use image::GenericImageView;
+        use crate::vfs::{pack, DirVfs, PakVfs};
+
+        let dir = std::env::temp_dir().join(format!("rt_tools_atlas_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for name in &["red", "blue"] {
+            make_texture(name.to_string())
+                .unwrap()
+                .save(dir.join(format!("{}.png", name)))
+                .unwrap();
+        }
+
+        let mut archive = Vec::new();
+        pack(&dir, &mut archive).unwrap();
+        let pak = PakVfs::from_reader(&*archive).unwrap();
+
+        for vfs in &[&DirVfs::new(&dir) as &dyn Vfs, &pak] {
+            let mut atlas = Atlas::new(SIZE);
+            atlas.add("red.png");
+            atlas.add("blue.png");
+
+            let map = atlas.stitch_from(*vfs).unwrap();
+            assert_eq!(map.map().get_pixel(0, 0), image::Rgba(RED));
+            assert_eq!(map.map().get_pixel(SIZE, 0), image::Rgba(BLUE));
+
+            let mut images = Resource::<image::DynamicImage>::new();
+            let (_, blue) = images.load_from(*vfs, "blue.png", &mut ()).unwrap();
+            assert_eq!(blue.get_pixel(0, 0), image::Rgba(BLUE));
+            assert!(images.load_from(*vfs, "green.png", &mut ()).is_err());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -5,6 +5,10 @@ pub enum Axis {
     Z,
 }
 
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+}
+
 impl std::convert::TryFrom<char> for Axis {
     type Error = ();
 
@@ -0,0 +1,132 @@
+use glm::{vec3, Vec3};
+
+use super::{
+    aabb::Aabb,
+    axis::Axis,
+    point::Point,
+    region::Region,
+    sides::*,
+};
+
+const EPSILON: f32 = 1e-5;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Movement {
+    pub offset: Vec3,
+    pub blocked: Sides,
+}
+
+// Collects the boxes of every cell in `region`, moved from block to world space
+pub fn boxes_in<F, I>(region: Region, mut shape: F) -> Vec<Aabb>
+    where
+        F: FnMut(Point) -> I,
+        I: IntoIterator<Item=Aabb>,
+{
+    region
+        .into_iter()
+        .flat_map(|p| {
+            let offset: Vec3 = p.into();
+            shape(p).into_iter().map(move |b| b.translate(offset))
+        })
+        .collect()
+}
+
+// Moves `body` by `motion` one axis at a time, Y first so it lands before sliding
+pub fn sweep(body: Aabb, motion: Vec3, boxes: &[Aabb]) -> Movement {
+    let mut body = body;
+    let mut offset = vec3(0., 0., 0.);
+    let mut blocked = Sides::empty();
+
+    for &axis in &[1, 0, 2] {
+        let d = motion[axis];
+
+        if d == 0. {
+            continue;
+        }
+
+        let clipped = boxes.iter().fold(d, |d, other| clip(&body, other, axis, d));
+
+        if clipped != d {
+            blocked = blocked | Side::from_axis(Axis::ALL[axis], d > 0.);
+        }
+
+        let mut step = vec3(0., 0., 0.);
+        step[axis] = clipped;
+        body = body.translate(step);
+        offset[axis] = clipped;
+    }
+
+    Movement { offset, blocked }
+}
+
+// Queries the cells the body passes through and sweeps it against them.
+// Cells are grown by one so boxes sticking out of their block, like fences, are found
+pub fn collide<F, I>(body: Aabb, motion: Vec3, shape: F) -> Movement
+    where
+        F: FnMut(Point) -> I,
+        I: IntoIterator<Item=Aabb>,
+{
+    let region = body.union(&body.translate(motion)).cells().expand(1);
+    sweep(body, motion, &boxes_in(region, shape))
+}
+
+fn clip(body: &Aabb, other: &Aabb, axis: usize, d: f32) -> f32 {
+    let overlaps = (0..3)
+        .filter(|&i| i != axis)
+        .all(|i| body.min[i] < other.max[i] - EPSILON && other.min[i] < body.max[i] - EPSILON);
+
+    if !overlaps {
+        d
+    } else if d > 0. && body.max[axis] <= other.min[axis] + EPSILON {
+        d.min((other.min[axis] - body.max[axis]).max(0.))
+    } else if d < 0. && body.min[axis] >= other.max[axis] - EPSILON {
+        d.max((other.max[axis] - body.min[axis]).min(0.))
+    } else {
+        d
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floor(p: Point) -> Vec<Aabb> {
+        match p {
+            Point(_, y, _) if y < 0 => vec![Aabb::unit()],
+            Point(2, 0, 0) => vec![Aabb::new(vec3(0., 0., 0.), vec3(1., 0.5, 1.))],
+            Point(0, 0, 2) => vec![Aabb::new(vec3(0.375, 0., 0.375), vec3(0.625, 1.5, 0.625))],
+            _ => vec![],
+        }
+    }
+
+    fn body() -> Aabb { Aabb::new(vec3(0.2, 0.5, 0.2), vec3(0.8, 2.3, 0.8)) }
+
+    #[test]
+    fn boxes() {
+        let boxes = boxes_in(Region::new(Point(0, -1, 0), Point(2, 0, 0)), floor);
+
+        assert_eq!(boxes.len(), 4);
+        assert!(boxes.contains(&Aabb::new(vec3(2., 0., 0.), vec3(3., 0.5, 1.))));
+        assert!(boxes.contains(&Aabb::new(vec3(1., -1., 0.), vec3(2., 0., 1.))));
+    }
+
+    #[test]
+    fn fall_and_slide() {
+        let movement = collide(body(), vec3(0.3, -1., 0.), floor);
+        assert_eq!(movement.offset, vec3(0.3, -0.5, 0.));
+        assert_eq!(movement.blocked, Sides::from(Down));
+
+        let landed = body().translate(vec3(0., -0.5, 0.));
+        let movement = collide(landed, vec3(2., 0., 0.), floor);
+        assert!((movement.offset.x - 1.2).abs() < 1e-5);
+        assert_eq!(movement.blocked, Sides::from(Left));
+
+        let movement = collide(landed, vec3(0., 0., 2.), floor);
+        assert!((movement.offset.z - 1.575).abs() < 1e-5);
+        assert_eq!(movement.blocked, Sides::from(Front));
+
+        let movement = collide(landed, vec3(0., 0.5, -1.), floor);
+        assert_eq!(movement.offset, vec3(0., 0.5, -1.));
+        assert!(movement.blocked.is_empty());
+    }
+}
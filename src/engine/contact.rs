@@ -0,0 +1,226 @@
+use glm::{vec2, Vec2, Vec3};
+
+use super::{
+    face::Face,
+    sides::*,
+};
+
+const EPSILON: f32 = 1e-5;
+
+fn plane(side: Side) -> (usize, f32) {
+    match side {
+        Front => (2, 1.),
+        Back => (2, 0.),
+        Up => (1, 1.),
+        Down => (1, 0.),
+        Left => (0, 1.),
+        Right => (0, 0.),
+    }
+}
+
+fn project(pos: &Vec3, axis: usize) -> Vec2 {
+    match axis {
+        0 => vec2(pos.y, pos.z),
+        1 => vec2(pos.x, pos.z),
+        _ => vec2(pos.x, pos.y),
+    }
+}
+
+fn lies_on(side: Side, pos: &Vec3) -> bool {
+    let (axis, value) = plane(side);
+    (pos[axis] - value).abs() < EPSILON
+}
+
+pub fn calc_contact<'a, I>(positions: I) -> Sides
+    where
+        I: IntoIterator<Item=&'a Vec3>,
+{
+    let positions: Vec<&Vec3> = positions.into_iter().collect();
+
+    if positions.is_empty() {
+        return Sides::empty();
+    }
+
+    Sides::all()
+        .into_iter()
+        .filter(|&side| positions.iter().all(|pos| lies_on(side, pos)))
+        .fold(Sides::empty(), |res, s| res | s)
+}
+
+pub fn calc_full_sides(faces: &[Face]) -> Sides {
+    Sides::all()
+        .into_iter()
+        .filter(|&side| {
+            let (axis, _) = plane(side);
+
+            let polygons: Vec<Vec<Vec2>> = faces
+                .iter()
+                .map(|f| f.vertexes.as_slice())
+                .filter(|vs| calc_contact(vs.iter().map(|v| &v.pos)).contains(side))
+                .map(|vs| vs.iter().map(|v| project(&v.pos, axis)).collect())
+                .collect();
+
+            covers_unit_square(&polygons)
+        })
+        .fold(Sides::empty(), |res, s| res | s)
+}
+
+fn edges(polygon: &[Vec2]) -> impl Iterator<Item=(Vec2, Vec2)> + '_ {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(&a, &b)| (a, b))
+}
+
+fn contains_point(polygon: &[Vec2], p: Vec2) -> bool {
+    edges(polygon)
+        .filter(|(a, b)| (a.y > p.y) != (b.y > p.y))
+        .filter(|(a, b)| p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y))
+        .count() % 2 == 1
+}
+
+fn intersection_x((a, b): (Vec2, Vec2), (c, d): (Vec2, Vec2)) -> Option<f32> {
+    let r = b - a;
+    let s = d - c;
+    let denom = r.x * s.y - r.y * s.x;
+
+    if denom.abs() < EPSILON {
+        return None;
+    }
+
+    let t = ((c.x - a.x) * s.y - (c.y - a.y) * s.x) / denom;
+    let u = ((c.x - a.x) * r.y - (c.y - a.y) * r.x) / denom;
+
+    if (0. ..=1.).contains(&t) && (0. ..=1.).contains(&u) {
+        Some(a.x + t * r.x)
+    } else {
+        None
+    }
+}
+
+fn sorted_breaks(mut values: Vec<f32>) -> Vec<f32> {
+    values.retain(|&v| (0. ..=1.).contains(&v));
+    values.push(0.);
+    values.push(1.);
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    values.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+    values
+}
+
+// Splits the square into vertical slabs at every vertex and edge crossing,
+// so inside a slab no two edges cross and every trapezoid between adjacent
+// edges is either fully covered by a polygon or not covered at all.
+fn covers_unit_square(polygons: &[Vec<Vec2>]) -> bool {
+    let all_edges: Vec<(Vec2, Vec2)> = polygons
+        .iter()
+        .flat_map(|p| edges(p))
+        .collect();
+
+    let mut xs: Vec<f32> = polygons
+        .iter()
+        .flatten()
+        .map(|v| v.x)
+        .collect();
+
+    for (i, &e) in all_edges.iter().enumerate() {
+        xs.extend(all_edges[i + 1..].iter().filter_map(|&f| intersection_x(e, f)));
+    }
+
+    let xs = sorted_breaks(xs);
+
+    xs
+        .windows(2)
+        .all(|w| {
+            let x = (w[0] + w[1]) * 0.5;
+
+            let ys = all_edges
+                .iter()
+                .filter(|(a, b)| a.x.min(b.x) <= w[0] && a.x.max(b.x) >= w[1])
+                .filter(|(a, b)| (a.x - b.x).abs() >= EPSILON)
+                .map(|(a, b)| a.y + (x - a.x) * (b.y - a.y) / (b.x - a.x))
+                .collect();
+
+            sorted_breaks(ys)
+                .windows(2)
+                .all(|h| {
+                    let p = vec2(x, (h[0] + h[1]) * 0.5);
+                    polygons.iter().any(|poly| contains_point(poly, p))
+                })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glm::vec3;
+    use crate::engine::{
+        face::FaceVertexes,
+        vertex::Vertex,
+    };
+
+    fn face(pos: &[[f32; 3]]) -> Face {
+        let vs: Vec<Vertex> = pos
+            .iter()
+            .map(|&[x, y, z]| Vertex {
+                pos: vec3(x, y, z),
+                st: vec2(0., 0.),
+                norm: vec3(0., 0., 0.),
+            })
+            .collect();
+
+        Face {
+            vertexes: FaceVertexes::from_slice(&vs).unwrap(),
+            contact: Sides::empty(),
+            layer: 0,
+            colors: vec![],
+            st2: vec![],
+            tint: None,
+        }
+    }
+
+    #[test]
+    fn contact() {
+        let c = calc_contact(&[vec3(0., 1., 0.), vec3(1., 1., 0.), vec3(1., 1., 1.)]);
+        assert_eq!(c, Up.into());
+
+        let c = calc_contact(&[vec3(0., 1., 0.), vec3(0., 1., 1.), vec3(0., 0.5, 1.)]);
+        assert_eq!(c, Right.into());
+
+        let c = calc_contact(&[vec3(0., 0., 0.), vec3(1., 0., 0.)]);
+        assert_eq!(c, Down | Back);
+
+        let c = calc_contact(&[vec3(0.5, 0.5, 0.5), vec3(1., 0., 0.), vec3(0., 1., 0.)]);
+        assert_eq!(c, Sides::empty());
+
+        let c = calc_contact(&[]);
+        assert_eq!(c, Sides::empty());
+    }
+
+    #[test]
+    fn full_sides() {
+        let up = face(&[[0., 1., 0.], [0., 1., 1.], [1., 1., 1.], [1., 1., 0.]]);
+        let down_a = face(&[[0., 0., 0.], [1., 0., 0.], [1., 0., 1.]]);
+        let down_b = face(&[[0., 0., 0.], [1., 0., 1.], [0., 0., 1.]]);
+        let front_half = face(&[[0., 0., 1.], [1., 0., 1.], [1., 0.5, 1.], [0., 0.5, 1.]]);
+        let slanted = face(&[[0., 0., 0.], [1., 1., 0.], [1., 1., 1.], [0., 0., 1.]]);
+
+        assert_eq!(calc_full_sides(&[up]), Up.into());
+        assert_eq!(calc_full_sides(&[down_a]), Sides::empty());
+        assert_eq!(calc_full_sides(&[front_half]), Sides::empty());
+        assert_eq!(calc_full_sides(&[slanted]), Sides::empty());
+
+        let down_a = face(&[[0., 0., 0.], [1., 0., 0.], [1., 0., 1.]]);
+        assert_eq!(calc_full_sides(&[down_a, down_b]), Down.into());
+    }
+
+    #[test]
+    fn full_sides_overlapping() {
+        let a = face(&[[0., 0., 0.], [0.6, 0., 0.], [0.6, 1., 0.], [0., 1., 0.]]);
+        let b = face(&[[0.4, 0., 0.], [1., 0., 0.], [1., 1., 0.], [0.4, 1., 0.]]);
+        let c = face(&[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]);
+        let d = face(&[[1., 0., 0.], [1., 1., 0.], [0.5, 0.5, 0.]]);
+
+        assert_eq!(calc_full_sides(&[a, b]), Back.into());
+        assert_eq!(calc_full_sides(&[c, d]), Sides::empty());
+    }
+}
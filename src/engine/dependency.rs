@@ -0,0 +1,259 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use super::{
+    handle::Handle,
+    tile::Tile,
+};
+
+pub enum Node<M, T> {
+    Tile(usize),
+    Model(Handle<M>),
+    Texture(Handle<T>),
+}
+
+impl<M, T> Clone for Node<M, T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<M, T> Copy for Node<M, T> {}
+
+impl<M, T> PartialEq for Node<M, T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Node::Tile(a), Node::Tile(b)) => a == b,
+            (Node::Model(a), Node::Model(b)) => a == b,
+            (Node::Texture(a), Node::Texture(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<M, T> Eq for Node<M, T> {}
+
+impl<M, T> Hash for Node<M, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Node::Tile(id) => (0u8, id).hash(state),
+            Node::Model(handle) => (1u8, handle).hash(state),
+            Node::Texture(handle) => (2u8, handle).hash(state),
+        }
+    }
+}
+
+impl<M, T> fmt::Debug for Node<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Tile(id) => f.debug_tuple("Tile").field(id).finish(),
+            Node::Model(handle) => f.debug_tuple("Model").field(handle).finish(),
+            Node::Texture(handle) => f.debug_tuple("Texture").field(handle).finish(),
+        }
+    }
+}
+
+pub struct DependencyGraph<M, T> {
+    dependencies: HashMap<Node<M, T>, HashSet<Node<M, T>>>,
+    dependents: HashMap<Node<M, T>, HashSet<Node<M, T>>>,
+}
+
+impl<M, T> DependencyGraph<M, T> {
+    pub fn new() -> Self { DependencyGraph::default() }
+
+    pub fn add(&mut self, dependent: Node<M, T>, dependency: Node<M, T>) {
+        self.dependencies.entry(dependent).or_default().insert(dependency);
+        self.dependents.entry(dependency).or_default().insert(dependent);
+    }
+
+    fn clear_dependencies(&mut self, node: Node<M, T>) {
+        for dependency in self.dependencies.remove(&node).unwrap_or_default() {
+            if let Some(set) = self.dependents.get_mut(&dependency) {
+                set.remove(&node);
+
+                if set.is_empty() {
+                    self.dependents.remove(&dependency);
+                }
+            }
+        }
+    }
+
+    pub fn remove(&mut self, node: Node<M, T>) {
+        self.clear_dependencies(node);
+
+        for dependent in self.dependents.remove(&node).unwrap_or_default() {
+            if let Some(set) = self.dependencies.get_mut(&dependent) {
+                set.remove(&node);
+
+                if set.is_empty() {
+                    self.dependencies.remove(&dependent);
+                }
+            }
+        }
+    }
+
+    // Replaces the edges of the tile, tiles that depend on it keep their edges
+    pub fn add_tile(&mut self, id: usize, tile: &Tile<M, T>) {
+        let node = Node::Tile(id);
+        self.clear_dependencies(node);
+
+        for state in &tile.states {
+            self.add(node, Node::Model(state.model));
+
+            for &layer in &state.layers {
+                self.add(node, Node::Texture(layer));
+            }
+        }
+    }
+
+    pub fn dependencies(&self, node: Node<M, T>) -> impl Iterator<Item=Node<M, T>> + '_ {
+        self.dependencies.get(&node).into_iter().flatten().copied()
+    }
+
+    pub fn dependents(&self, node: Node<M, T>) -> impl Iterator<Item=Node<M, T>> + '_ {
+        self.dependents.get(&node).into_iter().flatten().copied()
+    }
+
+    pub fn tiles_using_model(&self, model: Handle<M>) -> Vec<usize> {
+        tiles(self.dependents(Node::Model(model)))
+    }
+
+    pub fn tiles_using_texture(&self, texture: Handle<T>) -> Vec<usize> {
+        tiles(self.dependents(Node::Texture(texture)))
+    }
+
+    pub fn invalidate<I>(&self, changed: I) -> HashSet<Node<M, T>>
+        where
+            I: IntoIterator<Item=Node<M, T>>,
+    {
+        let mut stack: Vec<Node<M, T>> = changed.into_iter().collect();
+        let mut invalid: HashSet<Node<M, T>> = stack.iter().copied().collect();
+
+        while let Some(node) = stack.pop() {
+            for dependent in self.dependents(node) {
+                if invalid.insert(dependent) {
+                    stack.push(dependent);
+                }
+            }
+        }
+
+        invalid
+    }
+
+    pub fn invalid_tiles<I>(&self, changed: I) -> Vec<usize>
+        where
+            I: IntoIterator<Item=Node<M, T>>,
+    { tiles(self.invalidate(changed)) }
+}
+
+impl<M, T> Default for DependencyGraph<M, T> {
+    fn default() -> Self {
+        DependencyGraph {
+            dependencies: HashMap::new(),
+            dependents: HashMap::new(),
+        }
+    }
+}
+
+impl<M, T> fmt::Debug for DependencyGraph<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DependencyGraph")
+            .field("dependencies", &self.dependencies)
+            .field("dependents", &self.dependents)
+            .finish()
+    }
+}
+
+fn tiles<M, T, I>(nodes: I) -> Vec<usize>
+    where
+        I: IntoIterator<Item=Node<M, T>>,
+{
+    let mut tiles: Vec<usize> = nodes
+        .into_iter()
+        .filter_map(|n| match n {
+            Node::Tile(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+
+    tiles.sort_unstable();
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        shell_transform::Shell,
+        sides::Side,
+        state::State,
+    };
+
+    struct Model;
+    struct Texture;
+
+    fn model(idx: usize) -> Handle<Model> { Handle::new(idx, 0) }
+
+    fn texture(idx: usize) -> Handle<Texture> { Handle::new(idx, 0) }
+
+    fn tile(states: &[(usize, &[usize])]) -> Tile<Model, Texture> {
+        Tile {
+            states: states
+                .iter()
+                .map(|&(m, layers)| State {
+                    model: model(m),
+                    shell: Shell::new(),
+                    layers: layers.iter().map(|&l| texture(l)).collect(),
+                    facing: Side::Front,
+                })
+                .collect(),
+            id: 0,
+        }
+    }
+
+    #[test]
+    fn tile_dependencies() {
+        let mut graph = DependencyGraph::new();
+        graph.add_tile(0, &tile(&[(0, &[0, 1]), (1, &[1])]));
+        graph.add_tile(1, &tile(&[(1, &[2])]));
+        graph.add_tile(2, &tile(&[(2, &[2, 3])]));
+
+        assert_eq!(graph.tiles_using_model(model(0)), [0]);
+        assert_eq!(graph.tiles_using_model(model(1)), [0, 1]);
+        assert_eq!(graph.tiles_using_model(model(3)), Vec::<usize>::new());
+        assert_eq!(graph.tiles_using_texture(texture(1)), [0]);
+        assert_eq!(graph.tiles_using_texture(texture(2)), [1, 2]);
+
+        graph.add_tile(1, &tile(&[(2, &[3])]));
+        assert_eq!(graph.tiles_using_model(model(1)), [0]);
+        assert_eq!(graph.tiles_using_texture(texture(3)), [1, 2]);
+    }
+
+    #[test]
+    fn invalidate() {
+        let mut graph = DependencyGraph::new();
+        graph.add_tile(0, &tile(&[(0, &[0])]));
+        graph.add_tile(1, &tile(&[(1, &[1])]));
+        graph.add(Node::Tile(2), Node::Tile(1));
+        graph.add(Node::Tile(3), Node::Tile(2));
+
+        assert_eq!(graph.invalid_tiles(vec![Node::Texture(texture(1))]), [1, 2, 3]);
+        assert_eq!(graph.invalid_tiles(vec![Node::Model(model(0))]), [0]);
+        assert_eq!(graph.invalid_tiles(vec![Node::Texture(texture(0)), Node::Tile(2)]), [0, 2, 3]);
+
+        let invalid = graph.invalidate(vec![Node::Model(model(1))]);
+        assert!(invalid.contains(&Node::Model(model(1))));
+        assert!(invalid.contains(&Node::Tile(3)));
+        assert_eq!(invalid.len(), 4);
+
+        graph.remove(Node::Tile(2));
+        assert_eq!(graph.invalid_tiles(vec![Node::Texture(texture(1))]), [1]);
+        assert_eq!(graph.dependencies(Node::Tile(3)).count(), 0);
+        assert_eq!(graph.dependents(Node::Tile(1)).count(), 0);
+
+        graph.remove(Node::Model(model(0)));
+        assert_eq!(graph.dependencies(Node::Tile(0)).collect::<Vec<_>>(), [Node::Texture(texture(0))]);
+        assert!(graph.dependencies.values().chain(graph.dependents.values()).all(|set| !set.is_empty()));
+    }
+}
@@ -1,12 +1,17 @@
+use glm::{vec2, Vec2, Vec3};
+
 use super::{
+    normal::calc_area_normal,
     sides::Sides,
-    vertex::Vertex,
+    transform::Transform,
+    vertex::{Vertex, VertexLayout},
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FaceVertexes {
     Triangle([Vertex; 3]),
     Square([Vertex; 4]),
+    Polygon(Vec<Vertex>),
 }
 
 impl FaceVertexes {
@@ -14,7 +19,24 @@ impl FaceVertexes {
         match vs {
             &[x, y, z] => Some(FaceVertexes::Triangle([x, y, z])),
             &[x, y, z, w] => Some(FaceVertexes::Square([x, y, z, w])),
-            _ => None,
+            &[] | &[_] | &[_, _] => None,
+            _ => Some(FaceVertexes::Polygon(vs.to_vec())),
+        }
+    }
+
+    pub fn as_slice(&self) -> &[Vertex] {
+        match self {
+            FaceVertexes::Triangle(vs) => vs,
+            FaceVertexes::Square(vs) => vs,
+            FaceVertexes::Polygon(vs) => vs,
+        }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [Vertex] {
+        match self {
+            FaceVertexes::Triangle(vs) => vs,
+            FaceVertexes::Square(vs) => vs,
+            FaceVertexes::Polygon(vs) => vs,
         }
     }
 }
@@ -29,19 +51,20 @@ impl From<[Vertex; 4]> for FaceVertexes {
 
 impl FaceVertexes {
     pub fn extend_vertexes(&self, vertexes: &mut Vec<Vertex>, indexes: &mut Vec<u32>) {
-        let index = indexes.len() as u32;
+        self.extend_indexes(vertexes.len() as u32, indexes);
+        vertexes.extend(self.as_slice());
+    }
 
+    pub fn extend_indexes(&self, index: u32, indexes: &mut Vec<u32>) {
         match self {
-            FaceVertexes::Triangle(vs) => {
-                vertexes.extend(vs);
+            FaceVertexes::Triangle(_) => {
                 indexes.extend(&[
                     index,
                     index + 1,
                     index + 2,
                 ]);
             }
-            FaceVertexes::Square(vs) => {
-                vertexes.extend(vs);
+            FaceVertexes::Square(_) => {
                 indexes.extend(&[
                     index,
                     index + 1,
@@ -51,15 +74,151 @@ impl FaceVertexes {
                     index + 3,
                 ]);
             }
+            FaceVertexes::Polygon(vs) => {
+                let positions: Vec<Vec3> = vs.iter().map(|v| v.pos).collect();
+                extend_polygon_indexes(&positions, index, indexes);
+            }
+        }
+    }
+
+    // Indexes into the vertexes after `transform`, listed back to front when it mirrors.
+    // A polygon is triangulated again in that order, its ears aren't symmetric
+    pub fn extend_transformed_indexes(&self, transform: &Transform, index: u32, indexes: &mut Vec<u32>) {
+        match self {
+            FaceVertexes::Polygon(vs) => {
+                let mut positions: Vec<Vec3> = vs.iter().map(|v| transform.apply_point(v.pos)).collect();
+
+                if transform.is_mirror() {
+                    positions.reverse();
+                }
+
+                extend_polygon_indexes(&positions, index, indexes);
+            }
+            _ => self.extend_indexes(index, indexes),
+        }
+    }
+}
+
+// An outline that can't be triangulated adds no triangles, `scheme::model` rejects those on load
+fn extend_polygon_indexes(positions: &[Vec3], index: u32, indexes: &mut Vec<u32>) {
+    if let Some(triangles) = triangulate(positions) {
+        indexes.extend(triangles.into_iter().map(|i| index + i));
+    }
+}
+
+const EPSILON: f32 = 1e-6;
+
+fn cross_2d(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// The corners themselves don't count, so a vertex repeated elsewhere in the outline doesn't block the ear.
+// A point on an edge still does, clipping past it would leave an outline that touches itself
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    let corner = |q: Vec2| (p - q).norm() <= EPSILON;
+
+    !(corner(a) || corner(b) || corner(c))
+        && cross_2d(a, b, p) >= -EPSILON && cross_2d(b, c, p) >= -EPSILON && cross_2d(c, a, p) >= -EPSILON
+}
+
+// Ear clipping of a planar outline. Returns `None` when no ear is left to clip,
+// which means the outline crosses itself
+pub fn triangulate(positions: &[Vec3]) -> Option<Vec<u32>> {
+    let normal = calc_area_normal(positions);
+    let abs = normal.abs();
+
+    let (u, v, sign) = match () {
+        _ if abs.x >= abs.y && abs.x >= abs.z => (1, 2, normal.x),
+        _ if abs.y >= abs.z => (2, 0, normal.y),
+        _ => (0, 1, normal.z),
+    };
+
+    let project = |p: &Vec3| if sign < 0. { vec2(p[v], p[u]) } else { vec2(p[u], p[v]) };
+
+    let points: Vec<Vec2> = positions.iter().map(project).collect();
+    let mut rest: Vec<usize> = (0..points.len()).collect();
+    let mut triangles: Vec<usize> = Vec::with_capacity(points.len().saturating_sub(2) * 3);
+
+    let corner = |rest: &[usize], i: usize| {
+        let n = rest.len();
+        (rest[(i + n - 1) % n], rest[i], rest[(i + 1) % n])
+    };
+
+    let area = |(a, b, c): (usize, usize, usize)| cross_2d(points[a], points[b], points[c]);
+
+    while rest.len() > 3 {
+        // Repeated vertexes and vertexes in the middle of a straight edge add no area
+        if let Some(i) = (0..rest.len()).find(|&i| area(corner(&rest, i)).abs() <= EPSILON) {
+            rest.remove(i);
+            continue;
+        }
+
+        let ear = (0..rest.len()).find(|&i| {
+            let (a, b, c) = corner(&rest, i);
+
+            area((a, b, c)) > 0. && rest
+                .iter()
+                .filter(|&&j| j != a && j != b && j != c)
+                .all(|&j| !in_triangle(points[j], points[a], points[b], points[c]))
+        })?;
+
+        let (a, b, c) = corner(&rest, ear);
+        triangles.extend(&[a, b, c]);
+        rest.remove(ear);
+    }
+
+    if rest.len() == 3 {
+        let last = corner(&rest, 1);
+
+        match area(last) {
+            a if a > EPSILON => triangles.extend(&[last.0, last.1, last.2]),
+            a if a < -EPSILON => return None,
+            _ => {}
         }
     }
+
+    Some(triangles.into_iter().map(|i| i as u32).collect())
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Face {
     pub vertexes: FaceVertexes,
     pub contact: Sides,
     pub layer: u32,
+    pub colors: Vec<glm::Vec4>,
+    pub st2: Vec<Vec2>,
+    pub tint: Option<u32>,
+}
+
+impl Face {
+    pub fn extend_vertexes<V>(&self, vertexes: &mut Vec<V>, indexes: &mut Vec<u32>)
+        where
+            V: VertexLayout,
+    {
+        self.vertexes.extend_indexes(vertexes.len() as u32, indexes);
+        V::extend_from_face(self, vertexes);
+    }
+
+    pub fn extend_transformed_vertexes<V>(&self, transform: &Transform, vertexes: &mut Vec<V>, indexes: &mut Vec<u32>)
+        where
+            V: VertexLayout,
+    {
+        self.vertexes.extend_transformed_indexes(transform, vertexes.len() as u32, indexes);
+        V::extend_from_transformed_face(self, transform, vertexes);
+    }
+
+    pub fn transform(&mut self, transform: &Transform) {
+        for v in self.vertexes.as_mut_slice() {
+            v.pos = transform.apply_point(v.pos);
+            v.norm = transform.apply_normal(v.norm);
+        }
+
+        if transform.is_mirror() {
+            self.vertexes.as_mut_slice().reverse();
+            self.colors.reverse();
+            self.st2.reverse();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -93,4 +252,136 @@ mod tests {
         assert_eq!(indexes.len(), 9);
         assert_eq!(indexes[3..=8], [3, 4, 5, 3, 5, 6]);
     }
+
+    #[test]
+    fn from_slice() {
+        let v = Vertex {
+            pos: vec3(0., 0., 0.),
+            st: vec2(0., 0.),
+            norm: vec3(0., 1., 0.),
+        };
+
+        assert_eq!(FaceVertexes::from_slice(&[v, v]), None);
+        assert_eq!(FaceVertexes::from_slice(&[v; 3]), Some(FaceVertexes::Triangle([v; 3])));
+        assert_eq!(FaceVertexes::from_slice(&[v; 4]), Some(FaceVertexes::Square([v; 4])));
+        assert_eq!(FaceVertexes::from_slice(&[v; 6]), Some(FaceVertexes::Polygon(vec![v; 6])));
+    }
+
+    #[test]
+    fn triangulate_convex() {
+        let hexagon: Vec<Vec3> = (0..6)
+            .map(|i| {
+                let a = i as f32 * std::f32::consts::PI / 3.;
+                vec3(a.cos(), 0., -a.sin())
+            })
+            .collect();
+
+        let ids = triangulate(&hexagon).unwrap();
+        assert_eq!(ids.len(), 12);
+
+        for t in ids.chunks(3) {
+            let n = (hexagon[t[1] as usize] - hexagon[t[0] as usize])
+                .cross(&(hexagon[t[2] as usize] - hexagon[t[0] as usize]));
+
+            assert!(n.y > 0.);
+        }
+    }
+
+    #[test]
+    fn triangulate_concave() {
+        let arrow = [
+            vec3(0., 0., 0.),
+            vec3(2., 0., 0.),
+            vec3(2., 2., 0.),
+            vec3(1., 1., 0.),
+            vec3(0., 2., 0.),
+        ];
+
+        assert_eq!(area(&arrow, &triangulate(&arrow).unwrap()), 3.);
+    }
+
+    #[test]
+    fn triangulate_collinear() {
+        let arrow = [
+            vec3(0., 0., 0.),
+            vec3(1., 0., 0.),
+            vec3(2., 0., 0.),
+            vec3(2., 2., 0.),
+            vec3(1., 1., 0.),
+            vec3(1., 1., 0.),
+            vec3(0., 2., 0.),
+        ];
+
+        assert_eq!(area(&arrow, &triangulate(&arrow).unwrap()), 3.);
+
+        let notch = [
+            vec3(0., 0., 0.),
+            vec3(1., 0., 0.),
+            vec3(1., 1., 0.),
+            vec3(0.75, 0.25, 0.),
+            vec3(0.5, 1., 0.),
+            vec3(0., 1., 0.),
+        ];
+
+        assert_eq!(area(&notch, &triangulate(&notch).unwrap()), 0.8125);
+
+        let squares = [
+            vec3(0., 0., 0.),
+            vec3(1., 0., 0.),
+            vec3(1., 1., 0.),
+            vec3(2., 1., 0.),
+            vec3(2., 2., 0.),
+            vec3(1., 2., 0.),
+            vec3(1., 1., 0.),
+            vec3(0., 1., 0.),
+        ];
+
+        assert_eq!(area(&squares, &triangulate(&squares).unwrap()), 2.);
+
+        let crossed = [
+            vec3(0., 0., 0.),
+            vec3(2., 0., 0.),
+            vec3(2., 2., 0.),
+            vec3(1., -1., 0.),
+            vec3(0., 2., 0.),
+        ];
+
+        assert_eq!(triangulate(&crossed), None);
+    }
+
+    // Total area of the triangles, each has to face +Z
+    fn area(positions: &[Vec3], ids: &[u32]) -> f32 {
+        ids
+            .chunks(3)
+            .map(|t| {
+                let n = (positions[t[1] as usize] - positions[t[0] as usize])
+                    .cross(&(positions[t[2] as usize] - positions[t[0] as usize]));
+
+                assert!(n.z > 0.);
+                n.z * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn extend_polygon() {
+        let mut vertexes = Vec::new();
+        let mut indexes = Vec::new();
+
+        let v = |x, y| Vertex {
+            pos: vec3(x, y, 0.),
+            st: vec2(0., 0.),
+            norm: vec3(0., 0., 1.),
+        };
+
+        let s = FaceVertexes::Square([v(0., 0.), v(1., 0.), v(1., 1.), v(0., 1.)]);
+        let p = FaceVertexes::Polygon(vec![v(0., 0.), v(2., 0.), v(2., 1.), v(1., 2.), v(0., 1.)]);
+
+        s.extend_vertexes(&mut vertexes, &mut indexes);
+        p.extend_vertexes(&mut vertexes, &mut indexes);
+
+        assert_eq!(vertexes.len(), 9);
+        assert_eq!(indexes.len(), 15);
+        assert!(indexes[6..].iter().all(|&i| (4..9).contains(&i)));
+    }
 }
@@ -0,0 +1,50 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+pub struct Handle<T> {
+    index: usize,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub(crate) fn new(index: usize, generation: u32) -> Self {
+        Handle {
+            index,
+            generation,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn index(self) -> usize { self.index }
+
+    pub fn generation(self) -> u32 { self.generation }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool { self.index == other.index && self.generation == other.generation }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
@@ -1,13 +1,25 @@
+pub mod aabb;
 pub mod atlas;
 pub mod axis;
+pub mod collision;
+pub mod contact;
+pub mod dependency;
 pub mod face;
+pub mod handle;
 pub mod model;
 pub mod normal;
+pub mod orientation;
+pub mod placement;
 pub mod point;
+pub mod raycast;
+pub mod region;
 pub mod resource;
+pub mod shared_resource;
 pub mod shell_transform;
 pub mod sides;
 pub mod sprite_map;
+pub mod streaming;
 pub mod state;
 pub mod tile;
+pub mod transform;
 pub mod vertex;
@@ -1,25 +1,194 @@
+use glm::Vec3;
+
 use super::{
+    aabb::Aabb,
     face::Face,
+    point::Point,
+    shell_transform::Shell,
     sides::Sides,
-    vertex::Vertex,
+    transform::Transform,
+    vertex::{ExtendedVertex, Vertex, VertexLayout},
 };
 
-#[derive(Debug, PartialEq)]
+// Lets rotated states whose model looks the same in both shells collapse into one
+pub trait Symmetry {
+    fn same_shape(&self, a: Shell, b: Shell) -> bool { a == b }
+}
+
+impl<T: Symmetry> Symmetry for std::rc::Rc<T> {
+    fn same_shape(&self, a: Shell, b: Shell) -> bool { (**self).same_shape(a, b) }
+}
+
+impl<T: Symmetry> Symmetry for std::sync::Arc<T> {
+    fn same_shape(&self, a: Shell, b: Shell) -> bool { (**self).same_shape(a, b) }
+}
+
+impl<T: Symmetry> Symmetry for Box<T> {
+    fn same_shape(&self, a: Shell, b: Shell) -> bool { (**self).same_shape(a, b) }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Model {
     pub faces: Vec<Face>,
     pub full_sides: Sides,
+    pub collision: Option<Vec<Aabb>>,
+    pub outline: Option<Vec<Aabb>>,
 }
 
 impl Model {
-    pub fn get_indexed_vertexes(&self) -> (Vec<Vertex>, Vec<u32>) {
+    pub fn get_indexed_vertexes(&self) -> (Vec<Vertex>, Vec<u32>) { self.get_indexed_vertexes_as() }
+
+    pub fn get_indexed_vertexes_as<V>(&self) -> (Vec<V>, Vec<u32>)
+        where
+            V: VertexLayout,
+    {
+        let min_capacity = self.faces.len() * 3;
+        let mut vertexes = Vec::with_capacity(min_capacity);
+        let mut indexes = Vec::with_capacity(min_capacity);
+
+        for face in &self.faces {
+            face.extend_vertexes(&mut vertexes, &mut indexes);
+        }
+
+        (vertexes, indexes)
+    }
+
+    pub fn transform(&mut self, transform: &Transform) {
+        for face in &mut self.faces {
+            face.transform(transform);
+        }
+    }
+
+    pub fn get_transformed_vertexes_as<V>(&self, transform: &Transform) -> (Vec<V>, Vec<u32>)
+        where
+            V: VertexLayout,
+    {
         let min_capacity = self.faces.len() * 3;
         let mut vertexes = Vec::with_capacity(min_capacity);
         let mut indexes = Vec::with_capacity(min_capacity);
 
         for face in &self.faces {
-            face.vertexes.extend_vertexes(&mut vertexes, &mut indexes);
+            face.extend_transformed_vertexes(transform, &mut vertexes, &mut indexes);
         }
 
         (vertexes, indexes)
     }
+
+    pub fn get_shell_vertexes_as<V>(&self, shell: Shell) -> (Vec<V>, Vec<u32>)
+        where
+            V: VertexLayout,
+    { self.get_transformed_vertexes_as(&shell.into()) }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        Aabb::from_points(self.faces
+            .iter()
+            .flat_map(|f| f.vertexes.as_slice())
+            .map(|v| v.pos)
+        )
+    }
+
+    pub fn get_shell_bounds(&self, shell: Shell) -> Option<Aabb> { self.bounds().map(|b| b.transform(&shell.into())) }
+
+    // Declared collision boxes, or the mesh bounds when the model doesn't declare any
+    pub fn collision_boxes(&self, shell: Shell) -> Vec<Aabb> { self.shell_boxes(self.collision.as_deref(), shell) }
+
+    pub fn outline_boxes(&self, shell: Shell) -> Vec<Aabb> { self.shell_boxes(self.outline.as_deref(), shell) }
+
+    pub fn get_outline_segments(&self, shell: Shell) -> Vec<(Vec3, Vec3)> {
+        self.outline_boxes(shell)
+            .iter()
+            .flat_map(|b| b.edges())
+            .collect()
+    }
+
+    fn shell_boxes(&self, declared: Option<&[Aabb]>, shell: Shell) -> Vec<Aabb> {
+        let transform = shell.into();
+
+        match declared {
+            Some(boxes) => boxes.iter().map(|b| b.transform(&transform)).collect(),
+            None => self.bounds().into_iter().map(|b| b.transform(&transform)).collect(),
+        }
+    }
+
+    pub fn get_tinted_vertexes<F>(&self, point: Point, tint: F) -> (Vec<ExtendedVertex>, Vec<u32>)
+        where
+            F: FnMut(u32, Point) -> glm::Vec4,
+    { self.tinted(point, tint, |face, vertexes, indexes| face.extend_vertexes(vertexes, indexes)) }
+
+    pub fn get_transformed_tinted_vertexes<F>(&self, transform: &Transform, point: Point, tint: F)
+                                              -> (Vec<ExtendedVertex>, Vec<u32>)
+        where
+            F: FnMut(u32, Point) -> glm::Vec4,
+    { self.tinted(point, tint, |face, vertexes, indexes| face.extend_transformed_vertexes(transform, vertexes, indexes)) }
+
+    pub fn get_shell_tinted_vertexes<F>(&self, shell: Shell, point: Point, tint: F) -> (Vec<ExtendedVertex>, Vec<u32>)
+        where
+            F: FnMut(u32, Point) -> glm::Vec4,
+    { self.get_transformed_tinted_vertexes(&shell.into(), point, tint) }
+
+    fn tinted<F, E>(&self, point: Point, mut tint: F, mut extend: E) -> (Vec<ExtendedVertex>, Vec<u32>)
+        where
+            F: FnMut(u32, Point) -> glm::Vec4,
+            E: FnMut(&Face, &mut Vec<ExtendedVertex>, &mut Vec<u32>),
+    {
+        let min_capacity = self.faces.len() * 3;
+        let mut vertexes: Vec<ExtendedVertex> = Vec::with_capacity(min_capacity);
+        let mut indexes = Vec::with_capacity(min_capacity);
+
+        for face in &self.faces {
+            let start = vertexes.len();
+            extend(face, &mut vertexes, &mut indexes);
+
+            if let Some(slot) = face.tint {
+                let color = tint(slot, point);
+
+                for v in &mut vertexes[start..] {
+                    v.color = v.color.component_mul(&color);
+                }
+            }
+        }
+
+        (vertexes, indexes)
+    }
+}
+
+impl Symmetry for Model {
+    fn same_shape(&self, a: Shell, b: Shell) -> bool {
+        a == b || (a.transform_sides(self.full_sides) == b.transform_sides(self.full_sides)
+            && outlines(self, a) == outlines(self, b))
+    }
+}
+
+// Faces as their layer and rounded corner positions and texture coordinates.
+// Each outline starts at its smallest corner so the winding is kept and the triangulation
+// doesn't matter, then faces are sorted so their order doesn't either
+fn outlines(model: &Model, shell: Shell) -> Vec<(u32, Vec<[i32; 5]>)> {
+    let transform = Transform::from(shell);
+    let round = |v: f32| (v * 4096.).round() as i32;
+
+    let mut res: Vec<(u32, Vec<[i32; 5]>)> = model.faces
+        .iter()
+        .map(|face| {
+            let mut corners: Vec<[i32; 5]> = face.vertexes
+                .as_slice()
+                .iter()
+                .map(|v| {
+                    let pos = transform.apply_point(v.pos);
+                    [round(pos.x), round(pos.y), round(pos.z), round(v.st.x), round(v.st.y)]
+                })
+                .collect();
+
+            if transform.is_mirror() {
+                corners.reverse();
+            }
+
+            let first = (0..corners.len()).min_by_key(|&i| corners[i]).unwrap_or(0);
+            corners.rotate_left(first);
+
+            (face.layer, corners)
+        })
+        .collect();
+
+    res.sort_unstable();
+    res
 }
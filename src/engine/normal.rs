@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+
+use super::face::FaceVertexes;
+
 pub fn calc_normal_vec(a: &glm::Vec3, b: &glm::Vec3, c: &glm::Vec3) -> glm::Vec3 {
     (b - a).cross(&(c - a)).normalize()
 }
@@ -8,3 +12,129 @@ pub fn calc_normal<A, B, C>(a: A, b: B, c: C) -> glm::Vec3
         B: Into<glm::Vec3>,
         C: Into<glm::Vec3>,
 { calc_normal_vec(&a.into(), &b.into(), &c.into()) }
+
+pub fn calc_area_normal(positions: &[glm::Vec3]) -> glm::Vec3 {
+    positions
+        .iter()
+        .zip(positions.iter().cycle().skip(1))
+        .fold(glm::Vec3::zeros(), |n, (a, b)| n + glm::vec3(
+            (a.y - b.y) * (a.z + b.z),
+            (a.z - b.z) * (a.x + b.x),
+            (a.x - b.x) * (a.y + b.y),
+        ))
+}
+
+pub fn calc_tangent(positions: &[glm::Vec3], sts: &[glm::Vec2], norm: &glm::Vec3) -> glm::Vec4 {
+    let e1 = positions[1] - positions[0];
+    let e2 = positions[2] - positions[0];
+    let d1 = sts[1] - sts[0];
+    let d2 = sts[2] - sts[0];
+    let r = d1.x * d2.y - d2.x * d1.y;
+
+    let (tangent, bitangent) = if r.abs() > f32::EPSILON {
+        ((e1 * d2.y - e2 * d1.y) / r, (e2 * d1.x - e1 * d2.x) / r)
+    } else {
+        (e1, norm.cross(&e1))
+    };
+
+    let tangent = tangent - norm * norm.dot(&tangent);
+
+    let tangent = if tangent.norm() > f32::EPSILON {
+        tangent.normalize()
+    } else {
+        let any = if norm.x.abs() < 0.9 { glm::vec3(1., 0., 0.) } else { glm::vec3(0., 1., 0.) };
+        norm.cross(&any).normalize()
+    };
+
+    let handedness = if norm.cross(&tangent).dot(&bitangent) < 0. { -1. } else { 1. };
+
+    glm::vec4(tangent.x, tangent.y, tangent.z, handedness)
+}
+
+fn position_key(pos: &glm::Vec3) -> [u32; 3] {
+    // Adding zero turns -0.0 into 0.0 so both land on the same key
+    [(pos.x + 0.).to_bits(), (pos.y + 0.).to_bits(), (pos.z + 0.).to_bits()]
+}
+
+pub fn smooth_normals<'a, I>(faces: I)
+    where
+        I: IntoIterator<Item=&'a mut FaceVertexes>,
+{
+    let mut faces: Vec<&mut FaceVertexes> = faces.into_iter().collect();
+    let mut sums: HashMap<[u32; 3], glm::Vec3> = HashMap::new();
+
+    for face in &faces {
+        let positions: Vec<glm::Vec3> = face.as_slice().iter().map(|v| v.pos).collect();
+        let normal = calc_area_normal(&positions);
+
+        for pos in &positions {
+            *sums.entry(position_key(pos)).or_insert_with(glm::Vec3::zeros) += normal;
+        }
+    }
+
+    for face in &mut faces {
+        for v in face.as_mut_slice() {
+            let sum = sums[&position_key(&v.pos)];
+
+            if sum.norm() > 0. {
+                v.norm = sum.normalize();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glm::{vec2, vec3};
+    use crate::engine::vertex::Vertex;
+
+    fn face(pos: &[[f32; 3]]) -> FaceVertexes {
+        let vs: Vec<Vertex> = pos
+            .iter()
+            .map(|&[x, y, z]| Vertex {
+                pos: vec3(x, y, z),
+                st: vec2(0., 0.),
+                norm: vec3(0., 0., 0.),
+            })
+            .collect();
+
+        FaceVertexes::from_slice(&vs).unwrap()
+    }
+
+    #[test]
+    fn area_normal() {
+        let n = calc_area_normal(&[vec3(0., 0., 0.), vec3(2., 0., 0.), vec3(2., 2., 0.), vec3(0., 2., 0.)]);
+        assert_eq!(n, vec3(0., 0., 8.));
+    }
+
+    #[test]
+    fn tangent() {
+        let positions = [vec3(0., 0., 0.), vec3(1., 0., 0.), vec3(1., 1., 0.)];
+        let norm = vec3(0., 0., 1.);
+
+        let sts = [vec2(0., 0.), vec2(1., 0.), vec2(1., 1.)];
+        assert_eq!(calc_tangent(&positions, &sts, &norm), glm::vec4(1., 0., 0., 1.));
+
+        let sts = [vec2(0., 0.), vec2(0., 1.), vec2(1., 1.)];
+        assert_eq!(calc_tangent(&positions, &sts, &norm), glm::vec4(0., 1., 0., -1.));
+
+        let sts = [vec2(0., 0.), vec2(0., 0.), vec2(0., 0.)];
+        let t = calc_tangent(&positions, &sts, &norm);
+        assert_eq!(t.xyz().dot(&norm), 0.);
+    }
+
+    #[test]
+    fn smooth() {
+        let mut top = face(&[[0., 1., 0.], [0., 1., 1.], [1., 1., 1.], [1., 1., 0.]]);
+        let mut side = face(&[[1., 0., 0.], [1., 1., 0.], [1., 1., 1.], [1., 0., 1.]]);
+
+        smooth_normals(vec![&mut top, &mut side]);
+
+        let edge = vec3(1., 1., 0.).normalize();
+        assert_eq!(top.as_slice()[0].norm, vec3(0., 1., 0.));
+        assert_eq!(top.as_slice()[2].norm, edge);
+        assert_eq!(side.as_slice()[0].norm, vec3(1., 0., 0.));
+        assert_eq!(side.as_slice()[1].norm, edge);
+    }
+}
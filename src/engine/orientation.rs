@@ -0,0 +1,384 @@
+use std::collections::{HashMap, VecDeque};
+
+use glm::{vec3, Vec3};
+
+use super::{
+    axis::Axis,
+    point::Point,
+    shell_transform::*,
+    sides::*,
+};
+
+const ACTIONS: [ShellTransformAction; 9] = [
+    ShellTransformAction::TurnCounterClockwise(Axis::X),
+    ShellTransformAction::TurnCounterClockwise(Axis::Y),
+    ShellTransformAction::TurnCounterClockwise(Axis::Z),
+    ShellTransformAction::TurnClockwise(Axis::X),
+    ShellTransformAction::TurnClockwise(Axis::Y),
+    ShellTransformAction::TurnClockwise(Axis::Z),
+    ShellTransformAction::Flip(Axis::X),
+    ShellTransformAction::Flip(Axis::Y),
+    ShellTransformAction::Flip(Axis::Z),
+];
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Orientation {
+    matrix: [[i8; 3]; 3],
+}
+
+impl Orientation {
+    pub fn identity() -> Self { Orientation { matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]] } }
+
+    pub fn from_matrix(matrix: [[i32; 3]; 3]) -> Option<Self> {
+        let mut res = [[0; 3]; 3];
+        let mut used = [false; 3];
+
+        for (row, src) in res.iter_mut().zip(&matrix) {
+            let mut entries = src.iter().enumerate().filter(|(_, &v)| v != 0);
+            let (col, &val) = entries.next()?;
+
+            if entries.next().is_some() || val.abs() != 1 || used[col] {
+                return None;
+            }
+
+            used[col] = true;
+            row[col] = val as i8;
+        }
+
+        Some(Orientation { matrix: res })
+    }
+
+    pub fn from_action(action: ShellTransformAction) -> Self {
+        let mut res = Self::identity();
+        apply_action(&mut res, action);
+        res
+    }
+
+    pub fn from_actions<I>(actions: I) -> Self
+        where
+            I: IntoIterator<Item=ShellTransformAction>,
+    { *apply_actions(&mut Self::identity(), actions) }
+
+    pub fn all() -> Vec<Self> {
+        const PERMUTATIONS: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+
+        PERMUTATIONS
+            .iter()
+            .flat_map(|perm| (0..8).map(move |signs| (perm, signs)))
+            .map(|(perm, signs)| {
+                let mut matrix = [[0; 3]; 3];
+
+                for (row, &col) in perm.iter().enumerate() {
+                    matrix[row][col] = if signs & (1 << row) != 0 { -1 } else { 1 };
+                }
+
+                Orientation { matrix }
+            })
+            .collect()
+    }
+
+    pub fn rotations() -> Vec<Self> {
+        Self::all()
+            .into_iter()
+            .filter(|o| !o.is_mirror())
+            .collect()
+    }
+
+    pub fn matrix(self) -> [[i32; 3]; 3] {
+        let mut res = [[0; 3]; 3];
+
+        for (row, src) in res.iter_mut().zip(&self.matrix) {
+            for (v, &s) in row.iter_mut().zip(src) {
+                *v = s as i32;
+            }
+        }
+
+        res
+    }
+
+    pub fn determinant(self) -> i32 {
+        let m = self.matrix();
+
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn is_mirror(self) -> bool { self.determinant() < 0 }
+
+    pub fn then(self, next: Self) -> Self { next * self }
+
+    pub fn inverse(self) -> Self {
+        let mut matrix = [[0; 3]; 3];
+
+        for (i, row) in self.matrix.iter().enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                matrix[j][i] = v;
+            }
+        }
+
+        Orientation { matrix }
+    }
+
+    pub fn apply(self, v: Vec3) -> Vec3 {
+        let m = &self.matrix;
+        let row = |i: usize| m[i][0] as f32 * v.x + m[i][1] as f32 * v.y + m[i][2] as f32 * v.z;
+
+        vec3(row(0), row(1), row(2))
+    }
+
+    pub fn apply_point(self, Point(x, y, z): Point) -> Point {
+        let m = self.matrix();
+        let row = |i: usize| m[i][0] * x + m[i][1] * y + m[i][2] * z;
+
+        Point(row(0), row(1), row(2))
+    }
+
+    pub fn side(self, side: Side) -> Side {
+        Side::from_normal(self.apply(side.normal())).expect("orientation maps sides to sides")
+    }
+
+    pub fn sides(self, sides: Sides) -> Sides {
+        sides
+            .into_iter()
+            .fold(Sides::empty(), |res, side| res | self.side(side))
+    }
+
+    pub fn actions(self) -> Vec<ShellTransformAction> {
+        let mut parents: HashMap<Orientation, (Orientation, ShellTransformAction)> = HashMap::new();
+        let mut queue = VecDeque::new();
+        let identity = Self::identity();
+        queue.push_back(identity);
+
+        while let Some(current) = queue.pop_front() {
+            if current == self {
+                break;
+            }
+
+            for &action in &ACTIONS {
+                let next = current.then(Self::from_action(action));
+
+                if next != identity && !parents.contains_key(&next) {
+                    parents.insert(next, (current, action));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut actions = Vec::new();
+        let mut current = self;
+
+        while let Some(&(parent, action)) = parents.get(&current) {
+            actions.push(action);
+            current = parent;
+        }
+
+        actions.reverse();
+        actions
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Rotations {
+    Horizontal,
+    All,
+    Axis,
+}
+
+impl Rotations {
+    pub fn orientations(self) -> Vec<Orientation> {
+        use ShellTransformAction::*;
+
+        match self {
+            Rotations::Horizontal => vec![
+                Orientation::identity(),
+                Orientation::from_action(TurnCounterClockwise(Axis::Y)),
+                Orientation::from_actions(vec![TurnCounterClockwise(Axis::Y); 2]),
+                Orientation::from_action(TurnClockwise(Axis::Y)),
+            ],
+            Rotations::All => Orientation::rotations(),
+            Rotations::Axis => vec![
+                Orientation::identity(),
+                Orientation::from_action(TurnClockwise(Axis::Z)),
+                Orientation::from_action(TurnCounterClockwise(Axis::X)),
+            ],
+        }
+    }
+
+    pub fn reference(self) -> Side {
+        match self {
+            Rotations::Horizontal | Rotations::All => Front,
+            Rotations::Axis => Up,
+        }
+    }
+}
+
+impl std::convert::TryFrom<&str> for Rotations {
+    type Error = ();
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "horizontal" => Ok(Rotations::Horizontal),
+            "all" => Ok(Rotations::All),
+            "axis" => Ok(Rotations::Axis),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Default for Orientation {
+    fn default() -> Self { Self::identity() }
+}
+
+impl std::ops::Mul for Orientation {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut matrix = [[0; 3]; 3];
+
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..3).map(|k| self.matrix[i][k] * rhs.matrix[k][j]).sum();
+            }
+        }
+
+        Orientation { matrix }
+    }
+}
+
+impl ShellTransform for Orientation {
+    fn flip(&mut self, axis: Axis) -> &mut Self {
+        let i = axis_index(axis);
+
+        for v in &mut self.matrix[i] {
+            *v = -*v;
+        }
+
+        self
+    }
+
+    fn turn_counter_clockwise(&mut self, axis: Axis) -> &mut Self {
+        let (a, b) = turn_plane(axis);
+        let m = &mut self.matrix;
+
+        let row_a = m[a];
+        m[a] = m[b].map(|v| -v);
+        m[b] = row_a;
+
+        self
+    }
+
+    fn turn_clockwise(&mut self, axis: Axis) -> &mut Self {
+        let (a, b) = turn_plane(axis);
+        let m = &mut self.matrix;
+
+        let row_a = m[a];
+        m[a] = m[b];
+        m[b] = row_a.map(|v| -v);
+
+        self
+    }
+}
+
+fn axis_index(axis: Axis) -> usize {
+    match axis {
+        Axis::X => 0,
+        Axis::Y => 1,
+        Axis::Z => 2,
+    }
+}
+
+// Counter-clockwise turn around `axis` maps the vector (.., a, b) to (.., -b, a)
+fn turn_plane(axis: Axis) -> (usize, usize) {
+    match axis {
+        Axis::X => (1, 2),
+        Axis::Y => (2, 0),
+        Axis::Z => (0, 1),
+    }
+}
+
+impl From<Orientation> for Shell {
+    fn from(orientation: Orientation) -> Self {
+        *apply_actions(&mut Shell::new(), orientation.actions())
+    }
+}
+
+impl From<Shell> for Orientation {
+    fn from(shell: Shell) -> Self {
+        let row = |side: Side| {
+            let Point(x, y, z) = shell.local_side(side).into();
+            [x, y, z]
+        };
+
+        Orientation::from_matrix([row(Left), row(Up), row(Front)])
+            .expect("shell is a cube symmetry")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group() {
+        let all = Orientation::all();
+        assert_eq!(all.len(), 48);
+        assert_eq!(Orientation::rotations().len(), 24);
+
+        for (i, a) in all.iter().enumerate() {
+            assert!(all[..i].iter().all(|b| a != b));
+            assert_eq!(*a * a.inverse(), Orientation::identity());
+            assert_eq!(Orientation::from_matrix(a.matrix()), Some(*a));
+
+            for b in &all {
+                assert!(all.contains(&(*a * *b)));
+                assert_eq!(a.then(*b).is_mirror(), a.is_mirror() != b.is_mirror());
+            }
+        }
+
+        assert!(Orientation::from_matrix([[1, 0, 0], [1, 0, 0], [0, 0, 1]]).is_none());
+        assert!(Orientation::from_matrix([[2, 0, 0], [0, 1, 0], [0, 0, 1]]).is_none());
+    }
+
+    #[test]
+    fn matches_vec3() {
+        let v = vec3(1., 2., 3.);
+
+        for &action in &ACTIONS {
+            let mut expected = v;
+            apply_action(&mut expected, action);
+
+            assert_eq!(Orientation::from_action(action).apply(v), expected);
+        }
+
+        let actions = [
+            ShellTransformAction::TurnCounterClockwise(Axis::Y),
+            ShellTransformAction::Flip(Axis::Z),
+            ShellTransformAction::TurnClockwise(Axis::X),
+        ];
+
+        let mut expected = v;
+        apply_actions(&mut expected, actions.iter().copied());
+        assert_eq!(Orientation::from_actions(actions.iter().copied()).apply(v), expected);
+    }
+
+    #[test]
+    fn shell() {
+        for o in Orientation::all() {
+            let actions = o.actions();
+            assert!(actions.len() <= 3);
+            assert_eq!(Orientation::from_actions(actions.iter().copied()), o);
+
+            let shell = Shell::from(o);
+            assert_eq!(shell, *apply_actions(&mut Shell::new(), actions));
+            assert_eq!(Orientation::from(shell), o);
+
+            for side in [Front, Back, Up, Down, Left, Right] {
+                assert_eq!(shell.local_side(o.side(side)), side);
+            }
+        }
+
+        assert!(Orientation::identity().actions().is_empty());
+        assert_eq!(Shell::from(Orientation::identity()), Shell::new());
+    }
+}
@@ -0,0 +1,74 @@
+use glm::Vec3;
+
+use super::{
+    orientation::Rotations,
+    sides::*,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Placement {
+    pub side: Side,
+    pub look: Vec3,
+    pub hit: Vec3,
+}
+
+impl Placement {
+    pub fn new(side: Side, look: Vec3, hit: Vec3) -> Self { Placement { side, look, hit } }
+
+    pub fn toward_player(&self) -> Side {
+        let look = if self.look.x.abs() >= self.look.z.abs() {
+            if self.look.x > 0. { Left } else { Right }
+        } else if self.look.z > 0. {
+            Front
+        } else {
+            Back
+        };
+
+        look.opposite()
+    }
+
+    pub fn is_upper_half(&self) -> bool {
+        match self.side {
+            Up => false,
+            Down => true,
+            _ => self.hit.y - self.hit.y.floor() > 0.5,
+        }
+    }
+
+    pub fn up(&self) -> Side {
+        if self.is_upper_half() { Down } else { Up }
+    }
+
+    pub fn facing(&self, rotations: Rotations) -> (Side, Side) {
+        match rotations {
+            Rotations::Horizontal => (self.toward_player(), Up),
+            Rotations::Axis => (self.side, Up),
+            Rotations::All => (self.toward_player(), self.up()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glm::vec3;
+
+    #[test]
+    fn facing() {
+        let look = vec3(0.2, -0.5, -1.);
+        let p = Placement::new(Left, look, vec3(3., 4.7, -2.));
+
+        assert_eq!(p.toward_player(), Front);
+        assert!(p.is_upper_half());
+        assert_eq!(p.facing(Rotations::Horizontal), (Front, Up));
+        assert_eq!(p.facing(Rotations::Axis), (Left, Up));
+        assert_eq!(p.facing(Rotations::All), (Front, Down));
+
+        let p = Placement::new(Up, vec3(-1., -1., 0.5), vec3(3., 5., -2.));
+        assert_eq!(p.toward_player(), Left);
+        assert_eq!(p.facing(Rotations::All), (Left, Up));
+
+        let p = Placement::new(Back, vec3(0., 0., 1.), vec3(3., 4.2, -2.));
+        assert_eq!(p.facing(Rotations::All), (Back, Up));
+    }
+}
@@ -1,5 +1,6 @@
 use std::iter::FromIterator;
 use super::{
+    region::Region,
     sides::*,
 };
 
@@ -14,6 +15,16 @@ impl Point {
 
     pub fn distance_between(self, other: Self) -> f32 { (self - other).distance() }
 
+    pub fn manhattan(self, other: Self) -> i32 {
+        let Point(x, y, z) = self - other;
+        x.abs() + y.abs() + z.abs()
+    }
+
+    pub fn chebyshev(self, other: Self) -> i32 {
+        let Point(x, y, z) = self - other;
+        x.abs().max(y.abs()).max(z.abs())
+    }
+
     pub fn modulo(self, m: i32) -> Self {
         let x = self.0 % m;
         let y = self.1 % m;
@@ -26,6 +37,34 @@ impl Point {
         )
     }
 
+    pub fn div_euclid(self, m: i32) -> Self { Point(self.0.div_euclid(m), self.1.div_euclid(m), self.2.div_euclid(m)) }
+
+    pub fn rem_euclid(self, m: i32) -> Self { Point(self.0.rem_euclid(m), self.1.rem_euclid(m), self.2.rem_euclid(m)) }
+
+    // Splits a world point into the chunk it belongs to and the position inside that chunk
+    pub fn chunk(self, size: i32) -> (Self, Self) { (self.div_euclid(size), self.rem_euclid(size)) }
+
+    pub fn min(self, other: Self) -> Self { Point(self.0.min(other.0), self.1.min(other.1), self.2.min(other.2)) }
+
+    pub fn max(self, other: Self) -> Self { Point(self.0.max(other.0), self.1.max(other.1), self.2.max(other.2)) }
+
+    pub fn neighbours_6(self) -> impl Iterator<Item=Self> { self.neighbours(1) }
+
+    pub fn neighbours_18(self) -> impl Iterator<Item=Self> { self.neighbours(2) }
+
+    pub fn neighbours_26(self) -> impl Iterator<Item=Self> { self.neighbours(3) }
+
+    // Neighbours differing from `self` in at most `axes` coordinates
+    fn neighbours(self, axes: i32) -> impl Iterator<Item=Self> {
+        Region::new(Point(-1, -1, -1), Point(1, 1, 1))
+            .into_iter()
+            .filter(move |&Point(x, y, z)| {
+                let n = x.abs() + y.abs() + z.abs();
+                n > 0 && n <= axes
+            })
+            .map(move |offset| self + offset)
+    }
+
     pub fn zero() -> Self { Point(0, 0, 0) }
 
     pub fn front() -> Self { Point(0, 0, 1) }
@@ -57,11 +96,7 @@ impl std::fmt::Display for Point {
 
 impl From<[f32; 3]> for Point {
     fn from([x, y, z]: [f32; 3]) -> Self {
-        Point(
-            if x < 0. { x - 1. } else { x } as i32,
-            if y < 0. { y - 1. } else { y } as i32,
-            if z < 0. { z - 1. } else { z } as i32,
-        )
+        Point(x.floor() as i32, y.floor() as i32, z.floor() as i32)
     }
 }
 
@@ -73,6 +108,18 @@ impl From<glm::Vec3> for Point {
     fn from(vec: glm::Vec3) -> Self { Point::from([vec.x, vec.y, vec.z]) }
 }
 
+impl From<glm::IVec3> for Point {
+    fn from(vec: glm::IVec3) -> Self { Point(vec.x, vec.y, vec.z) }
+}
+
+impl From<Point> for glm::IVec3 {
+    fn from(Point(x, y, z): Point) -> Self { glm::vec3(x, y, z) }
+}
+
+impl From<Point> for glm::Vec3 {
+    fn from(Point(x, y, z): Point) -> Self { glm::vec3(x as f32, y as f32, z as f32) }
+}
+
 impl From<Side> for Point {
     fn from(side: Side) -> Self {
         match side {
@@ -116,6 +163,22 @@ impl std::ops::SubAssign for Point {
     fn sub_assign(&mut self, rhs: Self) { *self = *self - rhs }
 }
 
+impl std::ops::Mul<i32> for Point {
+    type Output = Self;
+
+    fn mul(self, m: i32) -> Self { Point(self.0 * m, self.1 * m, self.2 * m) }
+}
+
+impl std::ops::MulAssign<i32> for Point {
+    fn mul_assign(&mut self, m: i32) { *self = *self * m }
+}
+
+impl std::ops::Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self { Point(-self.0, -self.1, -self.2) }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,6 +198,38 @@ mod tests {
         assert_eq!(p.modulo(5), Point(2, 0, 4));
     }
 
+    #[test]
+    fn arithmetic() {
+        let p = Point(3, -2, 1);
+
+        assert_eq!(p * 2, Point(6, -4, 2));
+        assert_eq!(-p, Point(-3, 2, -1));
+        assert_eq!(p.manhattan(Point::zero()), 6);
+        assert_eq!(p.chebyshev(Point(0, 1, 1)), 3);
+
+        let (chunk, local) = Point(17, -1, -16).chunk(16);
+        assert_eq!(chunk, Point(1, -1, -1));
+        assert_eq!(local, Point(1, 15, 0));
+        assert_eq!(chunk * 16 + local, Point(17, -1, -16));
+    }
+
+    #[test]
+    fn neighbours() {
+        let p = Point(5, 0, -3);
+
+        assert_eq!(p.neighbours_6().count(), 6);
+        assert_eq!(p.neighbours_18().count(), 18);
+        assert_eq!(p.neighbours_26().count(), 26);
+
+        assert!(p.neighbours_6().all(|n| n.manhattan(p) == 1));
+        assert!(p.neighbours_18().all(|n| n.chebyshev(p) == 1 && n.manhattan(p) <= 2));
+        assert!(!p.neighbours_26().any(|n| n == p));
+
+        for side in Side::ALL.iter().copied() {
+            assert!(p.neighbours_6().any(|n| n == p.to(side)));
+        }
+    }
+
     #[test]
     fn to() {
         let p = Point::zero()
@@ -158,7 +253,14 @@ mod tests {
         let p = Point::from((-12.1, 3., 5.1));
         assert_eq!(p, Point(-13, 3, 5));
 
+        let p = Point::from((-1., -2., 0.));
+        assert_eq!(p, Point(-1, -2, 0));
+
         let p = Point::from(Up);
         assert_eq!(p, Point(0, 1, 0));
+
+        let p = Point::from(glm::vec3(4, -5, 6));
+        assert_eq!(glm::IVec3::from(p), glm::vec3(4, -5, 6));
+        assert_eq!(glm::Vec3::from(p), glm::vec3(4., -5., 6.));
     }
 }
@@ -0,0 +1,282 @@
+use glm::Vec3;
+
+use super::{
+    axis::Axis,
+    model::Model,
+    point::Point,
+    shell_transform::Shell,
+    sides::*,
+    vertex::Vertex,
+};
+
+const EPSILON: f32 = 1e-6;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hit {
+    pub point: Point,
+    pub side: Side,
+    pub distance: f32,
+    pub position: Vec3,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    origin: Vec3,
+    dir: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, dir: Vec3) -> Option<Self> {
+        if glm::length(&dir) < EPSILON {
+            return None;
+        }
+
+        Some(Ray { origin, dir: glm::normalize(&dir) })
+    }
+
+    pub fn origin(&self) -> Vec3 { self.origin }
+
+    pub fn dir(&self) -> Vec3 { self.dir }
+
+    pub fn at(&self, distance: f32) -> Vec3 { self.origin + self.dir * distance }
+
+    pub fn cells(&self, max_distance: f32) -> Cells { Cells::new(*self, max_distance) }
+
+    pub fn cast<F>(&self, max_distance: f32, mut solid: F) -> Option<Hit>
+        where
+            F: FnMut(Point) -> bool,
+    { self.cells(max_distance).find(|hit| solid(hit.point)) }
+
+    // `test` gets every visited cell and decides whether the ray stops there,
+    // e.g. by refining the hit against the model placed in that cell
+    pub fn cast_with<F>(&self, max_distance: f32, test: F) -> Option<Hit>
+        where
+            F: FnMut(Hit) -> Option<Hit>,
+    {
+        self.cells(max_distance)
+            .filter_map(test)
+            .find(|hit| hit.distance <= max_distance)
+    }
+
+    pub fn intersect_model(&self, point: Point, model: &Model, shell: Shell) -> Option<Hit> {
+        let offset: Vec3 = point.into();
+        let (vertexes, indexes) = model.get_shell_vertexes_as::<Vertex>(shell);
+
+        indexes
+            .chunks_exact(3)
+            .filter_map(|tri| {
+                let pos = |i: u32| vertexes[i as usize].pos + offset;
+                self.intersect_triangle(pos(tri[0]), pos(tri[1]), pos(tri[2]))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(distance, normal)| Hit {
+                point,
+                side: Side::from_normal(normal).unwrap_or(Up),
+                distance,
+                position: self.at(distance),
+            })
+    }
+
+    // Möller–Trumbore, both windings count so double-sided faces like flowers are hit too.
+    // Returns the distance and the triangle normal turned toward the ray origin
+    fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<(f32, Vec3)> {
+        let ab = b - a;
+        let ac = c - a;
+        let p = glm::cross(&self.dir, &ac);
+        let det = glm::dot(&ab, &p);
+
+        if det.abs() < EPSILON {
+            return None;
+        }
+
+        let inv = 1. / det;
+        let to_origin = self.origin - a;
+        let u = glm::dot(&to_origin, &p) * inv;
+
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+
+        let q = glm::cross(&to_origin, &ab);
+        let v = glm::dot(&self.dir, &q) * inv;
+
+        if v < 0. || u + v > 1. {
+            return None;
+        }
+
+        let distance = glm::dot(&ac, &q) * inv;
+
+        if distance < 0. {
+            return None;
+        }
+
+        let normal = glm::cross(&ab, &ac);
+        let normal = if glm::dot(&normal, &self.dir) > 0. { -normal } else { normal };
+
+        Some((distance, normal))
+    }
+}
+
+// Grid cells pierced by a ray in order, using the Amanatides & Woo DDA walk
+#[derive(Clone, Debug)]
+pub struct Cells {
+    ray: Ray,
+    cell: [i32; 3],
+    step: [i32; 3],
+    next: [f32; 3],
+    delta: [f32; 3],
+    side: Side,
+    distance: f32,
+    max_distance: f32,
+}
+
+impl Cells {
+    fn new(ray: Ray, max_distance: f32) -> Self {
+        let Point(x, y, z) = ray.origin.into();
+        let cell = [x, y, z];
+        let mut step = [0; 3];
+        let mut next = [f32::INFINITY; 3];
+        let mut delta = [f32::INFINITY; 3];
+
+        for i in 0..3 {
+            let (o, d) = (ray.origin[i], ray.dir[i]);
+
+            if d > 0. {
+                step[i] = 1;
+                next[i] = (cell[i] as f32 + 1. - o) / d;
+                delta[i] = 1. / d;
+            } else if d < 0. {
+                step[i] = -1;
+                next[i] = (o - cell[i] as f32) / -d;
+                delta[i] = -1. / d;
+            }
+        }
+
+        // The origin cell isn't entered through any face, so report the one the ray faces away from
+        let main = (0..3)
+            .max_by(|&a, &b| ray.dir[a].abs().total_cmp(&ray.dir[b].abs()))
+            .unwrap_or(0);
+
+        Cells {
+            ray,
+            cell,
+            step,
+            next,
+            delta,
+            side: entered_side(main, step[main]),
+            distance: 0.,
+            max_distance,
+        }
+    }
+}
+
+impl Iterator for Cells {
+    type Item = Hit;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.distance > self.max_distance {
+            return None;
+        }
+
+        let [x, y, z] = self.cell;
+        let hit = Hit {
+            point: Point(x, y, z),
+            side: self.side,
+            distance: self.distance,
+            position: self.ray.at(self.distance),
+        };
+
+        let axis = (0..3)
+            .min_by(|&a, &b| self.next[a].total_cmp(&self.next[b]))
+            .unwrap_or(0);
+
+        self.distance = self.next[axis];
+        self.cell[axis] += self.step[axis];
+        self.next[axis] += self.delta[axis];
+        self.side = entered_side(axis, self.step[axis]);
+
+        Some(hit)
+    }
+}
+
+// Moving along +X enters the next cell through its -X face
+fn entered_side(axis: usize, step: i32) -> Side { Side::from_axis(Axis::ALL[axis], step < 0) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glm::vec3;
+    use crate::{
+        engine::shell_transform::ShellTransform,
+        load::LoadBytes,
+    };
+
+    #[test]
+    fn cells() {
+        let ray = Ray::new(vec3(0.5, 0.5, 0.5), vec3(1., 0., 0.)).unwrap();
+        let cells: Vec<_> = ray.cells(3.).collect();
+
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells[0].point, Point(0, 0, 0));
+        assert_eq!(cells[3].point, Point(3, 0, 0));
+        assert!(cells[1..].iter().all(|hit| hit.side == Right));
+        assert_eq!(cells[2].distance, 1.5);
+        assert_eq!(cells[2].position, vec3(2., 0.5, 0.5));
+
+        let ray = Ray::new(vec3(-0.5, 2.7, 0.5), vec3(1., -1., 0.)).unwrap();
+        let hit = ray.cast(10., |p| p.1 < 0).unwrap();
+        assert_eq!(hit.point, Point(2, -1, 0));
+        assert_eq!(hit.side, Up);
+        assert!((hit.distance - 2.7 * 2f32.sqrt()).abs() < 1e-4);
+
+        let ray = Ray::new(vec3(-0.5, -0.5, -0.5), vec3(0., 0., -1.)).unwrap();
+        let points: Vec<_> = ray.cells(2.).map(|hit| hit.point).collect();
+        assert_eq!(points, [Point(-1, -1, -1), Point(-1, -1, -2), Point(-1, -1, -3)]);
+
+        assert!(Ray::new(vec3(0., 0., 0.), vec3(0., 0., 0.)).is_none());
+        assert!(ray.cast(2., |_| false).is_none());
+    }
+
+    #[test]
+    fn intersect_model() {
+        let code = br#"
+        faces:
+          - pos:
+              - [ 0.0, 0.5, 1.0 ]
+              - [ 1.0, 0.5, 1.0 ]
+              - [ 1.0, 0.5, 0.0 ]
+              - [ 0.0, 0.5, 0.0 ]
+            st: [ [ 0.0, 0.0 ], [ 1.0, 0.0 ], [ 1.0, 1.0 ], [ 0.0, 1.0 ] ]
+          - pos:
+              - [ 0.0, 0.0, 0.0 ]
+              - [ 1.0, 0.0, 0.0 ]
+              - [ 1.0, 0.0, 1.0 ]
+              - [ 0.0, 0.0, 1.0 ]
+            st: [ [ 0.0, 0.0 ], [ 1.0, 0.0 ], [ 1.0, 1.0 ], [ 0.0, 1.0 ] ]
+        "#;
+        let slab = Model::load_bytes(code, &mut ()).unwrap();
+        let slab_at = Point(2, 0, 0);
+
+        let ray = Ray::new(vec3(2.5, 3., 0.3), vec3(0., -1., 0.)).unwrap();
+        let hit = ray.cast_with(10., |hit| match hit.point {
+            p if p == slab_at => ray.intersect_model(p, &slab, Shell::new()),
+            _ => None,
+        }).unwrap();
+
+        assert_eq!(hit.point, slab_at);
+        assert_eq!(hit.side, Up);
+        assert!((hit.distance - 2.5).abs() < 1e-5);
+
+        let mut upper = Shell::new();
+        upper.flip(Axis::Y);
+        let hit = ray.intersect_model(slab_at, &slab, upper).unwrap();
+        assert!((hit.distance - 2.).abs() < 1e-5);
+
+        let ray = Ray::new(vec3(2.5, 0.75, -1.), vec3(0., 0., 1.)).unwrap();
+        assert!(ray.intersect_model(slab_at, &slab, Shell::new()).is_none());
+        assert!(ray.cast_with(10., |hit| ray.intersect_model(hit.point, &slab, Shell::new())).is_none());
+
+        let ray = Ray::new(vec3(2.5, 0.75, 0.5), vec3(0., -1., 0.)).unwrap();
+        assert_eq!(ray.intersect_model(slab_at, &slab, Shell::new()).unwrap().side, Up);
+    }
+}
@@ -0,0 +1,157 @@
+use super::point::Point;
+
+// Axis-aligned box of grid points, both corners inclusive
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Region {
+    min: Point,
+    max: Point,
+}
+
+impl Region {
+    pub fn new(a: Point, b: Point) -> Self { Region { min: a.min(b), max: a.max(b) } }
+
+    pub fn point(p: Point) -> Self { Region { min: p, max: p } }
+
+    pub fn around(center: Point, radius: i32) -> Self {
+        let r = Point(radius, radius, radius);
+        Region::new(center - r, center + r)
+    }
+
+    pub fn min(self) -> Point { self.min }
+
+    pub fn max(self) -> Point { self.max }
+
+    pub fn size(self) -> Point { self.max - self.min + Point(1, 1, 1) }
+
+    pub fn volume(self) -> usize {
+        let Point(x, y, z) = self.size();
+        x as usize * y as usize * z as usize
+    }
+
+    pub fn contains(self, Point(x, y, z): Point) -> bool {
+        let (min, max) = (self.min, self.max);
+
+        (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y) && (min.2..=max.2).contains(&z)
+    }
+
+    pub fn contains_region(self, other: Self) -> bool { self.contains(other.min) && self.contains(other.max) }
+
+    pub fn intersects(self, other: Self) -> bool { self.intersection(other).is_some() }
+
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        let min = self.min.max(other.min);
+        let max = self.max.min(other.max);
+
+        if min.0 > max.0 || min.1 > max.1 || min.2 > max.2 {
+            None
+        } else {
+            Some(Region { min, max })
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self { Region { min: self.min.min(other.min), max: self.max.max(other.max) } }
+
+    pub fn expand(self, n: u32) -> Self {
+        let n = n as i32;
+        Region { min: self.min - Point(n, n, n), max: self.max + Point(n, n, n) }
+    }
+
+    // None when the region is too thin to lose `n` cells on each side
+    pub fn shrink(self, n: u32) -> Option<Self> {
+        let n = n as i32;
+        let min = self.min + Point(n, n, n);
+        let max = self.max - Point(n, n, n);
+
+        if min.0 > max.0 || min.1 > max.1 || min.2 > max.2 {
+            None
+        } else {
+            Some(Region { min, max })
+        }
+    }
+
+    pub fn translate(self, offset: Point) -> Self { Region { min: self.min + offset, max: self.max + offset } }
+
+    pub fn iter(self) -> RegionIter { self.into_iter() }
+}
+
+impl From<Point> for Region {
+    fn from(p: Point) -> Self { Region::point(p) }
+}
+
+impl IntoIterator for Region {
+    type Item = Point;
+    type IntoIter = RegionIter;
+
+    fn into_iter(self) -> Self::IntoIter { RegionIter { region: self, next: Some(self.min) } }
+}
+
+// Walks X first, then Y, then Z
+#[derive(Clone, Debug)]
+pub struct RegionIter {
+    region: Region,
+    next: Option<Point>,
+}
+
+impl Iterator for RegionIter {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        let Region { min, max } = self.region;
+        let mut p = current;
+
+        p.0 += 1;
+
+        if p.0 > max.0 {
+            p.0 = min.0;
+            p.1 += 1;
+        }
+
+        if p.1 > max.1 {
+            p.1 = min.1;
+            p.2 += 1;
+        }
+
+        self.next = if p.2 > max.2 { None } else { Some(p) };
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region() {
+        let a = Region::new(Point(2, 0, 1), Point(0, 1, -1));
+        assert_eq!(a.min(), Point(0, 0, -1));
+        assert_eq!(a.max(), Point(2, 1, 1));
+        assert_eq!(a.size(), Point(3, 2, 3));
+        assert_eq!(a.volume(), 18);
+
+        let points: Vec<_> = a.iter().collect();
+        assert_eq!(points.len(), 18);
+        assert_eq!(points[0], Point(0, 0, -1));
+        assert_eq!(points[1], Point(1, 0, -1));
+        assert_eq!(points[3], Point(0, 1, -1));
+        assert_eq!(points[17], Point(2, 1, 1));
+        assert!(points.iter().all(|&p| a.contains(p)));
+        assert!(!a.contains(Point(3, 0, 0)));
+
+        let b = Region::around(Point(2, 1, 1), 1);
+        assert_eq!(a.intersection(b), Some(Region::new(Point(1, 0, 0), Point(2, 1, 1))));
+        assert!(a.union(b).contains_region(a));
+        assert!(a.union(b).contains_region(b));
+
+        let c = a.translate(Point(3, 0, 0));
+        assert!(!a.intersects(c));
+        assert!(a.expand(1).intersects(c));
+        assert_eq!(Region::from(Point(1, 2, 3)).iter().collect::<Vec<_>>(), [Point(1, 2, 3)]);
+
+        assert_eq!(a.expand(0), a);
+        assert_eq!(a.expand(2).shrink(2), Some(a));
+        assert_eq!(b.shrink(1), Some(Region::point(Point(2, 1, 1))));
+        assert_eq!(b.shrink(2), None);
+        assert_eq!(a.shrink(1), None);
+    }
+}
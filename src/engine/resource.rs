@@ -1,39 +1,129 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    collections::HashMap,
+    fmt,
+    time::SystemTime,
+};
 
-use super::super::{
-    get::{Get, GetMut},
-    load::Load,
-    asset::Asset,
+use super::{
+    handle::Handle,
+    super::{
+        get::{Get, GetMut},
+        load::{Load, LoadBytes, ReadError},
+        asset::{Asset, AssetRoot},
+        vfs::Vfs,
+        error::asset::AssetError,
+    },
 };
 
-type LoadResult<'a, T, E> = Result<(usize, &'a T), E>;
+type LoadResult<'a, T, E> = Result<(Handle<T>, &'a T), E>;
+
+#[derive(Debug)]
+struct Source {
+    file: String,
+    modified: Option<SystemTime>,
+}
+
+impl Source {
+    fn new(file: String) -> Self {
+        let modified = modified_time(&file);
+        Source { file, modified }
+    }
+}
+
+fn modified_time(file: &str) -> Option<SystemTime> {
+    std::fs::metadata(file)
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    item: Option<T>,
+    source: Source,
+    generation: u32,
+    refs: usize,
+}
+
+pub struct Reloaded<T, E> {
+    pub changed: Vec<Handle<T>>,
+    pub failed: Vec<(Handle<T>, E)>,
+}
+
+impl<T, E> Reloaded<T, E> {
+    pub fn is_empty(&self) -> bool { self.changed.is_empty() && self.failed.is_empty() }
+}
+
+impl<T, E: PartialEq> PartialEq for Reloaded<T, E> {
+    fn eq(&self, other: &Self) -> bool { self.changed == other.changed && self.failed == other.failed }
+}
+
+impl<T, E: fmt::Debug> fmt::Debug for Reloaded<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reloaded")
+            .field("changed", &self.changed)
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
 
 #[derive(Debug)]
 pub struct Resource<T> {
-    items: Vec<T>,
+    slots: Vec<Slot<T>>,
     files: HashMap<String, usize>,
+    free: Vec<usize>,
+    root: AssetRoot,
 }
 
 impl<T> Resource<T> {
     pub fn new() -> Self { Resource::default() }
 
+    fn insert(&mut self, item: T, source: Source) -> usize {
+        let file = source.file.clone();
+
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                let slot = &mut self.slots[idx];
+                slot.item = Some(item);
+                slot.source = source;
+                slot.refs = 0;
+                idx
+            }
+            None => {
+                self.slots.push(Slot {
+                    item: Some(item),
+                    source,
+                    generation: 0,
+                    refs: 0,
+                });
+
+                self.slots.len() - 1
+            }
+        };
+
+        self.files.insert(file, idx);
+        idx
+    }
+
+    fn item(&self, idx: usize) -> &T {
+        self.slots[idx].item.as_ref().expect("loaded slot")
+    }
+
     pub fn load_with<S>(&mut self, file: S, loader: &mut T::Loader) -> LoadResult<T, T::Error>
         where
             S: Into<String>,
             T: Load,
     {
-        let idx = match self.files.entry(file.into()) {
-            Entry::Occupied(en) => *en.get(),
-            Entry::Vacant(en) => {
-                let item = T::load(en.key(), loader)?;
-                let id = self.items.len();
-
-                self.items.push(item);
-                *en.insert(id)
+        let file = file.into();
+
+        let idx = match self.files.get(&file) {
+            Some(&idx) => idx,
+            None => {
+                let item = T::load(&file, loader)?;
+                self.insert(item, Source::new(file))
             }
         };
 
-        Ok((idx, &self.items[idx]))
+        Ok((Handle::new(idx, self.slots[idx].generation), self.item(idx)))
     }
 
     pub fn load<S>(&mut self, file: S) -> LoadResult<T, T::Error>
@@ -42,18 +132,189 @@ impl<T> Resource<T> {
             T: Load<Loader=()>,
     { self.load_with(file, &mut ()) }
 
-    pub fn load_asset_with<S>(&mut self, file: S, loader: &mut T::Loader) -> LoadResult<T, T::Error>
+    pub fn root(&self) -> &AssetRoot { &self.root }
+
+    pub fn set_root(&mut self, root: AssetRoot) { self.root = root }
+
+    pub fn with_root(root: AssetRoot) -> Self { Resource { root, ..Resource::default() } }
+
+    pub fn load_asset_with<S>(&mut self, file: S, loader: &mut T::Loader) -> LoadResult<T, AssetError<T::Error>>
         where
             S: AsRef<str>,
             T: Asset,
-    { self.load_with(T::full_path(file), loader) }
+    {
+        let path = self.root.locate::<T, _>(file)?;
+        self.load_with(path, loader).map_err(AssetError::LoadError)
+    }
 
-    pub fn load_asset<S>(&mut self, file: S) -> LoadResult<T, T::Error>
+    pub fn load_asset<S>(&mut self, file: S) -> LoadResult<T, AssetError<T::Error>>
         where
             S: AsRef<str>,
             T: Asset<Loader=()>,
     { self.load_asset_with(file, &mut ()) }
 
+    pub fn load_asset_in<S>(&mut self, root: &AssetRoot, file: S, loader: &mut T::Loader)
+                            -> LoadResult<'_, T, AssetError<T::Error>>
+        where
+            S: AsRef<str>,
+            T: Asset,
+    {
+        let path = root.locate::<T, _>(file)?;
+        self.load_with(path, loader).map_err(AssetError::LoadError)
+    }
+
+    pub fn load_from<V, S>(&mut self, vfs: &V, file: S, loader: &mut T::Loader)
+                           -> LoadResult<'_, T, ReadError<T::Error>>
+        where
+            V: Vfs + ?Sized,
+            S: Into<String>,
+            T: LoadBytes,
+    {
+        let file = file.into();
+
+        let idx = match self.files.get(&file) {
+            Some(&idx) => idx,
+            None => {
+                let item = T::load_from(vfs, &file, loader)?;
+                self.insert(item, Source { file, modified: None })
+            }
+        };
+
+        Ok((Handle::new(idx, self.slots[idx].generation), self.item(idx)))
+    }
+
+    pub fn acquire_with<S>(&mut self, file: S, loader: &mut T::Loader) -> Result<Handle<T>, T::Error>
+        where
+            S: Into<String>,
+            T: Load,
+    {
+        let (handle, _) = self.load_with(file, loader)?;
+        self.slots[handle.index()].refs += 1;
+
+        Ok(handle)
+    }
+
+    pub fn acquire<S>(&mut self, file: S) -> Result<Handle<T>, T::Error>
+        where
+            S: Into<String>,
+            T: Load<Loader=()>,
+    { self.acquire_with(file, &mut ()) }
+
+    fn slot(&self, handle: Handle<T>) -> Option<&Slot<T>> {
+        self.slots
+            .get(handle.index())
+            .filter(|s| s.item.is_some() && s.generation == handle.generation())
+    }
+
+    fn slot_mut(&mut self, handle: Handle<T>) -> Option<&mut Slot<T>> {
+        self.slots
+            .get_mut(handle.index())
+            .filter(|s| s.item.is_some() && s.generation == handle.generation())
+    }
+
+    pub fn is_valid(&self, handle: Handle<T>) -> bool { self.slot(handle).is_some() }
+
+    pub fn refs(&self, handle: Handle<T>) -> Option<usize> { self.slot(handle).map(|s| s.refs) }
+
+    pub fn retain(&mut self, handle: Handle<T>) -> bool {
+        match self.slot_mut(handle) {
+            Some(slot) => {
+                slot.refs += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn release(&mut self, handle: Handle<T>) -> bool {
+        match self.slot_mut(handle) {
+            Some(slot) => {
+                slot.refs = slot.refs.saturating_sub(1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove(&mut self, idx: usize) -> Option<T> {
+        let slot = &mut self.slots[idx];
+        let item = slot.item.take()?;
+
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.refs = 0;
+        self.files.remove(&slot.source.file);
+        self.free.push(idx);
+
+        Some(item)
+    }
+
+    pub fn unload(&mut self, handle: Handle<T>) -> Option<T> {
+        self.slot(handle)?;
+        self.remove(handle.index())
+    }
+
+    // Handles of the evicted items, they are stale once this returns
+    pub fn evict_unused(&mut self) -> Vec<Handle<T>> {
+        let unused: Vec<Handle<T>> = self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.item.is_some() && s.refs == 0)
+            .map(|(idx, s)| Handle::new(idx, s.generation))
+            .collect();
+
+        for handle in &unused {
+            self.remove(handle.index());
+        }
+
+        unused
+    }
+
+    // Items that fail to reload keep their previous value and are retried on the next pass
+    pub fn reload_changed_with(&mut self, loader: &mut T::Loader) -> Reloaded<T, T::Error>
+        where
+            T: Load,
+    {
+        let mut res = Reloaded { changed: Vec::new(), failed: Vec::new() };
+
+        for (idx, slot) in self.slots.iter_mut().enumerate() {
+            if slot.item.is_none() {
+                continue;
+            }
+
+            let modified = modified_time(&slot.source.file);
+
+            if modified.is_none() || modified == slot.source.modified {
+                continue;
+            }
+
+            let handle = Handle::new(idx, slot.generation);
+
+            match T::load(&slot.source.file, loader) {
+                Ok(item) => {
+                    slot.item = Some(item);
+                    slot.source.modified = modified;
+                    res.changed.push(handle);
+                }
+                Err(e) => res.failed.push((handle, e)),
+            }
+        }
+
+        res
+    }
+
+    pub fn reload_changed(&mut self) -> Reloaded<T, T::Error>
+        where
+            T: Load<Loader=()>,
+    { self.reload_changed_with(&mut ()) }
+
+    pub fn path(&self, handle: Handle<T>) -> Option<&str> {
+        self.slot(handle).map(|s| s.source.file.as_str())
+    }
+
+    pub fn modified(&self, handle: Handle<T>) -> Option<SystemTime> {
+        self.slot(handle).and_then(|s| s.source.modified)
+    }
+
     pub fn get<B>(&self, by: B) -> Option<&T>
         where
             Self: Get<B, Item=T>,
@@ -64,17 +325,30 @@ impl<T> Resource<T> {
             Self: GetMut<B, Item=T>,
     { GetMut::get_mut(self, by) }
 
-    pub fn len(&self) -> usize { self.items.len() }
+    pub fn len(&self) -> usize { self.slots.len() - self.free.len() }
 
-    pub fn iter(&self) -> std::slice::Iter<T> { self.items.iter() }
-    pub fn iter_mut(&mut self) -> std::slice::IterMut<T> { self.items.iter_mut() }
-    pub fn into_iter(self) -> std::vec::IntoIter<T> { self.items.into_iter() }
+    pub fn iter(&self) -> impl Iterator<Item=&T> { self.slots.iter().filter_map(|s| s.item.as_ref()) }
+    pub fn entries(&self) -> impl Iterator<Item=(Handle<T>, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, s)| Some((Handle::new(idx, s.generation), s.item.as_ref()?)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut T> { self.slots.iter_mut().filter_map(|s| s.item.as_mut()) }
+    pub fn into_iter(self) -> std::vec::IntoIter<T> {
+        self.slots
+            .into_iter()
+            .filter_map(|s| s.item)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
 }
 
-impl<T> Get<usize> for Resource<T> {
+impl<T> Get<Handle<T>> for Resource<T> {
     type Item = T;
 
-    fn get(&self, by: usize) -> Option<&Self::Item> { self.items.get(by) }
+    fn get(&self, by: Handle<T>) -> Option<&Self::Item> { self.slot(by)?.item.as_ref() }
 }
 
 impl<T> Get<&str> for Resource<T> {
@@ -82,7 +356,7 @@ impl<T> Get<&str> for Resource<T> {
 
     fn get(&self, by: &str) -> Option<&Self::Item> {
         let &id = self.files.get(by)?;
-        self.get(id)
+        self.slots[id].item.as_ref()
     }
 }
 
@@ -92,10 +366,10 @@ impl<T> Get<String> for Resource<T> {
     fn get(&self, by: String) -> Option<&Self::Item> { self.get(by.as_str()) }
 }
 
-impl<T> GetMut<usize> for Resource<T> {
+impl<T> GetMut<Handle<T>> for Resource<T> {
     type Item = T;
 
-    fn get_mut(&mut self, by: usize) -> Option<&mut Self::Item> { self.items.get_mut(by) }
+    fn get_mut(&mut self, by: Handle<T>) -> Option<&mut Self::Item> { self.slot_mut(by)?.item.as_mut() }
 }
 
 impl<T> GetMut<&str> for Resource<T> {
@@ -103,7 +377,7 @@ impl<T> GetMut<&str> for Resource<T> {
 
     fn get_mut(&mut self, by: &str) -> Option<&mut Self::Item> {
         let &id = self.files.get(by)?;
-        self.get_mut(id)
+        self.slots[id].item.as_mut()
     }
 }
 
@@ -116,22 +390,16 @@ impl<T> GetMut<String> for Resource<T> {
 impl<T> Default for Resource<T> {
     fn default() -> Self {
         Resource {
-            items: Vec::new(),
+            slots: Vec::new(),
             files: HashMap::new(),
+            free: Vec::new(),
+            root: AssetRoot::default(),
         }
     }
 }
 
-impl<T> AsRef<[T]> for Resource<T> {
-    fn as_ref(&self) -> &[T] { &*self.items }
-}
-
-impl<T> AsMut<[T]> for Resource<T> {
-    fn as_mut(&mut self) -> &mut [T] { &mut *self.items }
-}
-
 impl<T> Into<Vec<T>> for Resource<T> {
-    fn into(self) -> Vec<T> { self.items }
+    fn into(self) -> Vec<T> { self.into_iter().collect() }
 }
 
 #[cfg(test)]
@@ -155,17 +423,17 @@ mod tests {
     #[test]
     fn load() {
         let mut res: Resource<Tile> = Resource::new();
-        res.load("one").unwrap();
-        res.load("two").unwrap();
-        res.load("two").unwrap();
-        res.load("one").unwrap();
-        res.load("one").unwrap();
-        res.load("three").unwrap();
-
-        assert_eq!(res.get(0).unwrap().0, "one");
-        assert_eq!(res.get(1).unwrap().0, "two");
-        assert_eq!(res.get(2).unwrap().0, "three");
-        assert!(res.get(3).is_none());
+        let one = res.load("one").unwrap().0;
+        let two = res.load("two").unwrap().0;
+        assert_eq!(res.load("two").unwrap().0, two);
+        assert_eq!(res.load("one").unwrap().0, one);
+        assert_eq!(res.load("one").unwrap().0, one);
+        let three = res.load("three").unwrap().0;
+
+        assert_eq!(res.get(one).unwrap().0, "one");
+        assert_eq!(res.get(two).unwrap().0, "two");
+        assert_eq!(res.get(three).unwrap().0, "three");
+        assert_eq!((one.index(), two.index(), three.index()), (0, 1, 2));
 
         assert_eq!(res.get("one").unwrap().0, "one");
         assert_eq!(res.get("two").unwrap().0, "two");
@@ -174,6 +442,10 @@ mod tests {
 
         assert_eq!(res.len(), 3);
 
+        let (handle, _) = res.load("two").unwrap();
+        assert_eq!(handle, two);
+        assert_eq!(res.get(handle).unwrap().0, "two");
+
         let files = ["one", "two", "three"];
         assert!(
             res
@@ -210,8 +482,7 @@ mod tests {
         let mut res: Resource<Rc<Tile>> = Resource::new();
 
         let ts: TileSet = TileSet::load("one two", &mut res).unwrap();
-        assert_eq!(res.items[0].0, "one");
-        assert_eq!(res.items[1].0, "two");
+        assert_eq!(res.iter().map(|t| t.0.as_str()).collect::<Vec<_>>(), ["one", "two"]);
         assert!(ts
             .tiles
             .iter()
@@ -220,9 +491,7 @@ mod tests {
         );
 
         let ts: TileSet = TileSet::load("three one two", &mut res).unwrap();
-        assert_eq!(res.items[0].0, "one");
-        assert_eq!(res.items[1].0, "two");
-        assert_eq!(res.items[2].0, "three");
+        assert_eq!(res.iter().map(|t| t.0.as_str()).collect::<Vec<_>>(), ["one", "two", "three"]);
         assert!(ts
             .tiles
             .iter()
@@ -230,4 +499,119 @@ mod tests {
             .all(|(a, &b)| a.0.as_str() == b)
         );
     }
+
+    #[test]
+    fn unload() {
+        let mut res: Resource<Tile> = Resource::new();
+        let one = res.acquire("one").unwrap();
+        let two = res.acquire("two").unwrap();
+        let three = res.load("three").unwrap().0;
+
+        assert_eq!(res.acquire("one").unwrap(), one);
+        assert_eq!(res.refs(one), Some(2));
+        assert_eq!(res.get(one).unwrap().0, "one");
+
+        assert_eq!(res.unload(two).unwrap().0, "two");
+        assert!(!res.is_valid(two));
+        assert!(res.get(two).is_none());
+        assert!(res.get("two").is_none());
+        assert!(res.unload(two).is_none());
+        assert!(!res.release(two));
+        assert_eq!(res.len(), 2);
+
+        let four = res.acquire("four").unwrap();
+        assert_eq!(four.index(), two.index());
+        assert_ne!(four, two);
+        assert!(res.get(two).is_none());
+        assert_eq!(res.get(four).unwrap().0, "four");
+
+        assert_eq!(res.evict_unused(), [three]);
+        assert!(res.get("three").is_none());
+        assert!(res.path(three).is_none());
+
+        assert!(res.release(one));
+        assert!(res.release(one));
+        assert_eq!(res.refs(one), Some(0));
+        assert!(res.retain(four));
+        assert_eq!(res.evict_unused(), [one]);
+
+        assert!(res.get(one).is_none());
+        assert_eq!(res.iter().map(|t| t.0.as_str()).collect::<Vec<_>>(), ["four"]);
+    }
+
+    #[derive(Debug)]
+    struct Text(String);
+
+    impl Load for Text {
+        type Error = String;
+        type Loader = ();
+
+        fn load<S>(file: S, _: &mut Self::Loader) -> Result<Self, Self::Error>
+            where
+                S: AsRef<str>,
+        {
+            match std::fs::read_to_string(file.as_ref()) {
+                Ok(text) if text != "bad" => Ok(Text(text)),
+                Ok(text) => Err(text),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn reload_changed() {
+        use std::{fs, time::Duration};
+
+        let dir = std::env::temp_dir().join(format!("rt_tools_reload_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let one = dir.join("one");
+        let two = dir.join("two");
+        fs::write(&one, "one").unwrap();
+        fs::write(&two, "two").unwrap();
+
+        let one = one.to_str().unwrap().to_owned();
+        let two = two.to_str().unwrap().to_owned();
+
+        let mut res: Resource<Tile> = Resource::new();
+        res.load(&*one).unwrap();
+        let two_handle = res.load(&*two).unwrap().0;
+        let virtual_handle = res.load("virtual").unwrap().0;
+
+        assert_eq!(res.path(two_handle), Some(two.as_str()));
+        assert!(res.modified(two_handle).is_some());
+        assert!(res.modified(virtual_handle).is_none());
+        assert!(res.reload_changed().is_empty());
+
+        let later = res.modified(two_handle).unwrap() + Duration::from_secs(10);
+        fs::File::options()
+            .write(true)
+            .open(&two)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        assert_eq!(res.reload_changed(), Reloaded { changed: vec![two_handle], failed: vec![] });
+        assert_eq!(res.modified(two_handle), Some(later));
+        assert_eq!(res.get(two_handle).unwrap().0, two);
+        assert_eq!(res.get(&*two).unwrap().0, two);
+        assert!(res.reload_changed().is_empty());
+
+        let mut texts: Resource<Text> = Resource::new();
+        let one_text = texts.load(&*one).unwrap().0;
+        let two_text = texts.load(&*two).unwrap().0;
+
+        for (file, text) in &[(&one, "bad"), (&two, "new two")] {
+            fs::write(file, text).unwrap();
+            let later = fs::metadata(file).unwrap().modified().unwrap() + Duration::from_secs(20);
+            fs::File::options().write(true).open(file).unwrap().set_modified(later).unwrap();
+        }
+
+        assert_eq!(texts.reload_changed(), Reloaded { changed: vec![two_text], failed: vec![(one_text, "bad".to_owned())] });
+        assert_eq!(texts.get(one_text).unwrap().0, "one");
+        assert_eq!(texts.get(two_text).unwrap().0, "new two");
+        assert_eq!(texts.reload_changed().failed.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,375 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+};
+
+use super::{
+    handle::Handle,
+    super::load::Load,
+};
+
+#[derive(Debug)]
+enum Slot {
+    Loading,
+    Ready(usize),
+}
+
+#[derive(Debug)]
+struct Inner<T> {
+    items: Vec<Arc<T>>,
+    files: HashMap<String, Slot>,
+}
+
+#[derive(Debug)]
+pub struct SharedResource<T> {
+    inner: Mutex<Inner<T>>,
+    ready: Condvar,
+}
+
+// Clears a `Loading` slot if the loader returns early or panics, so threads
+// waiting on the same file don't block forever
+struct LoadingGuard<'a, T> {
+    res: &'a SharedResource<T>,
+    file: Option<&'a str>,
+}
+
+impl<T> Drop for LoadingGuard<'_, T> {
+    fn drop(&mut self) {
+        if let Some(file) = self.file {
+            self.res.lock().files.remove(file);
+
+            self.res.ready.notify_all();
+        }
+    }
+}
+
+impl<T> SharedResource<T> {
+    pub fn new() -> Self { SharedResource::default() }
+
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Items are never removed, so every handle keeps the first generation
+    pub fn load_with<S>(&self, file: S, loader: &mut T::Loader) -> Result<(Handle<T>, Arc<T>), T::Error>
+        where
+            S: Into<String>,
+            T: Load,
+    {
+        let file = file.into();
+        let mut inner = self.lock();
+
+        loop {
+            match inner.files.get(&file) {
+                Some(&Slot::Ready(idx)) => return Ok((Handle::new(idx, 0), Arc::clone(&inner.items[idx]))),
+                Some(Slot::Loading) => inner = self.ready.wait(inner).unwrap_or_else(|e| e.into_inner()),
+                None => break,
+            }
+        }
+
+        inner.files.insert(file.clone(), Slot::Loading);
+        drop(inner);
+
+        let mut guard = LoadingGuard { res: self, file: Some(&file) };
+        let item = Arc::new(T::load(&file, loader)?);
+        guard.file = None;
+
+        let mut inner = self.lock();
+        let idx = inner.items.len();
+        inner.items.push(Arc::clone(&item));
+        inner.files.insert(file.clone(), Slot::Ready(idx));
+        drop(inner);
+
+        self.ready.notify_all();
+        Ok((Handle::new(idx, 0), item))
+    }
+
+    pub fn load<S>(&self, file: S) -> Result<(Handle<T>, Arc<T>), T::Error>
+        where
+            S: Into<String>,
+            T: Load<Loader=()>,
+    { self.load_with(file, &mut ()) }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<Arc<T>> { self.lock().items.get(handle.index()).cloned() }
+
+    pub fn get_by_path(&self, file: &str) -> Option<Arc<T>> {
+        let inner = self.lock();
+
+        match inner.files.get(file)? {
+            &Slot::Ready(idx) => Some(Arc::clone(&inner.items[idx])),
+            Slot::Loading => None,
+        }
+    }
+
+    pub fn len(&self) -> usize { self.lock().items.len() }
+
+    pub fn is_empty(&self) -> bool { self.lock().items.is_empty() }
+
+    pub fn to_vec(&self) -> Vec<Arc<T>> { self.lock().items.clone() }
+}
+
+impl<T> Default for SharedResource<T> {
+    fn default() -> Self {
+        SharedResource {
+            inner: Mutex::new(Inner {
+                items: Vec::new(),
+                files: HashMap::new(),
+            }),
+            ready: Condvar::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BatchError {
+    pub file: String,
+    pub error: Box<dyn Debug + Send>,
+}
+
+type Job<'a> = Box<dyn FnOnce() -> Result<(), BatchError> + Send + 'a>;
+
+#[derive(Default)]
+pub struct Batch<'a> {
+    jobs: Vec<Job<'a>>,
+}
+
+impl<'a> Batch<'a> {
+    pub fn new() -> Self { Batch::default() }
+
+    pub fn add_with<T, S>(&mut self, res: &'a SharedResource<T>, file: S, mut loader: T::Loader) -> &mut Self
+        where
+            T: Load + Send + Sync + 'a,
+            T::Loader: Send + 'a,
+            T::Error: Debug + Send + 'static,
+            S: Into<String>,
+    {
+        let file = file.into();
+
+        self.jobs.push(Box::new(move || {
+            res.load_with(file.as_str(), &mut loader)
+                .map(|_| ())
+                .map_err(|e| BatchError { file, error: Box::new(e) })
+        }));
+
+        self
+    }
+
+    // Jobs sharing `loader` run their loads one at a time, but what they load through it
+    // is shared too, like the models and textures of tiles
+    pub fn add_shared<T, S>(&mut self, res: &'a SharedResource<T>, file: S, loader: &'a Mutex<T::Loader>) -> &mut Self
+        where
+            T: Load + Send + Sync + 'a,
+            T::Loader: Send + 'a,
+            T::Error: Debug + Send + 'static,
+            S: Into<String>,
+    {
+        let file = file.into();
+
+        self.jobs.push(Box::new(move || {
+            let mut loader = loader.lock().unwrap_or_else(|e| e.into_inner());
+
+            res.load_with(file.as_str(), &mut *loader)
+                .map(|_| ())
+                .map_err(|e| BatchError { file, error: Box::new(e) })
+        }));
+
+        self
+    }
+
+    pub fn add<T, S>(&mut self, res: &'a SharedResource<T>, file: S) -> &mut Self
+        where
+            T: Load<Loader=()> + Send + Sync + 'a,
+            T::Error: Debug + Send + 'static,
+            S: Into<String>,
+    { self.add_with(res, file, ()) }
+
+    pub fn len(&self) -> usize { self.jobs.len() }
+
+    pub fn is_empty(&self) -> bool { self.jobs.is_empty() }
+
+    pub fn run(self, threads: usize) -> Vec<BatchError> {
+        let workers = threads.max(1).min(self.jobs.len());
+        let jobs = Mutex::new(self.jobs);
+        let errors = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let job = match jobs.lock().unwrap_or_else(|e| e.into_inner()).pop() {
+                        Some(job) => job,
+                        None => break,
+                    };
+
+                    if let Err(e) = job() {
+                        errors.lock().unwrap_or_else(|e| e.into_inner()).push(e);
+                    }
+                });
+            }
+        });
+
+        let mut errors = errors.into_inner().unwrap_or_else(|e| e.into_inner());
+        errors.sort_by(|a, b| a.file.cmp(&b.file));
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TILE_LOADS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug)]
+    struct Tile(String);
+
+    impl Load for Tile {
+        type Error = String;
+        type Loader = ();
+
+        fn load<S>(file: S, _: &mut Self::Loader) -> Result<Self, Self::Error>
+            where
+                S: AsRef<str>,
+        {
+            TILE_LOADS.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+
+            match file.as_ref() {
+                f if f.starts_with("bad") => Err(f.to_owned()),
+                f => Ok(Tile(f.to_owned())),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Texture(String);
+
+    impl Load for Texture {
+        type Error = ();
+        type Loader = String;
+
+        fn load<S>(file: S, prefix: &mut Self::Loader) -> Result<Self, Self::Error>
+            where
+                S: AsRef<str>,
+        {
+            match file.as_ref() {
+                f if f.starts_with("bad") => Err(()),
+                f => Ok(Texture(format!("{}{}", prefix, f))),
+            }
+        }
+    }
+
+    #[test]
+    fn batch() {
+        let tiles: SharedResource<Tile> = SharedResource::new();
+        let textures: SharedResource<Texture> = SharedResource::new();
+
+        let mut batch = Batch::new();
+
+        for _ in 0..4 {
+            for file in &["stone", "dirt", "grass", "bad_tile"] {
+                batch.add(&tiles, *file);
+            }
+        }
+
+        batch.add_with(&textures, "stone.png", "hd/".to_owned());
+        batch.add_with(&textures, "stone.png", "hd/".to_owned());
+
+        assert_eq!(batch.len(), 18);
+        let errors = batch.run(4);
+
+        assert_eq!(tiles.len(), 3);
+        assert_eq!(textures.len(), 1);
+        assert_eq!(textures.get_by_path("stone.png").unwrap().0, "hd/stone.png");
+        assert_eq!(tiles.get_by_path("dirt").unwrap().0, "dirt");
+        assert!(tiles.get_by_path("bad_tile").is_none());
+
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|e| e.file == "bad_tile"));
+        assert_eq!(TILE_LOADS.load(Ordering::SeqCst), 3 + errors.len());
+
+        let (handle, tile) = tiles.load("stone").unwrap();
+        assert_eq!(tile.0, "stone");
+        assert!(Arc::ptr_eq(&tile, &tiles.get(handle).unwrap()));
+        assert_eq!(tiles.load("stone").unwrap().0, handle);
+    }
+
+    #[test]
+    fn batch_tiles() {
+        use crate::engine::{atlas::TexturePath, model::Model, resource::Resource, tile::Tile};
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("rt_tools_shared_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = |file: &str| dir.join(file).to_string_lossy().into_owned();
+
+        let model = "{ pos: [[0, 0, 0], [1, 0, 0], [0, 1, 0]], st: [[0, 0], [1, 0], [0, 1]], \
+                     faces: [{ data: { pos: [0, 1, 2], st: [0, 1, 2] } }] }";
+        fs::write(path("tri.yml"), model).unwrap();
+
+        for (tile, texture) in [("one.yml", "red.png"), ("two.yml", "blue.png")] {
+            let code = format!(
+                "{{ models: [ '{}' ], textures: [ '{}', '{}' ], states: [ {{ model: 0, layers: [ 0, 1 ] }} ] }}",
+                path("tri.yml"),
+                path(texture),
+                path("grid.png"),
+            );
+            fs::write(path(tile), code).unwrap();
+        }
+
+        let tiles: SharedResource<Tile<Model, TexturePath>> = SharedResource::new();
+        let loader = Mutex::new((Resource::new(), Resource::new()));
+        let mut batch = Batch::new();
+
+        for _ in 0..4 {
+            for tile in ["one.yml", "two.yml", "missing.yml"] {
+                batch.add_shared(&tiles, path(tile), &loader);
+            }
+        }
+
+        let errors = batch.run(4);
+        assert_eq!(errors.len(), 4);
+        assert!(errors.iter().all(|e| e.file == path("missing.yml")));
+
+        let (models, textures) = loader.into_inner().unwrap();
+        let one = tiles.get_by_path(&path("one.yml")).unwrap();
+        let two = tiles.get_by_path(&path("two.yml")).unwrap();
+
+        assert_eq!(tiles.len(), 2);
+        assert_eq!((models.len(), textures.len()), (1, 3));
+        assert_eq!(one.states[0].model, two.states[0].model);
+        assert_eq!(one.states[0].layers[1], two.states[0].layers[1]);
+        assert_eq!(models.refs(one.states[0].model), Some(2));
+        assert_eq!(textures.refs(one.states[0].layers[1]), Some(2));
+        assert_eq!(textures.get(two.states[0].layers[0]).unwrap().0, path("blue.png"));
+        assert_eq!(models.get(one.states[0].model).unwrap().faces.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn poisoned() {
+        let textures: SharedResource<Texture> = SharedResource::new();
+
+        std::thread::scope(|scope| {
+            let res = scope.spawn(|| {
+                let _inner = textures.lock();
+                panic!("poison the lock");
+            }).join();
+
+            assert!(res.is_err());
+        });
+
+        assert!(textures.inner.is_poisoned());
+
+        // A failed load must clear its slot, otherwise the retry would wait forever
+        for _ in 0..2 {
+            assert!(textures.load_with("bad.png", &mut String::new()).is_err());
+            assert!(textures.get_by_path("bad.png").is_none());
+        }
+
+        assert_eq!(textures.load_with("stone.png", &mut String::new()).unwrap().1.0, "stone.png");
+    }
+}
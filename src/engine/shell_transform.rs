@@ -2,6 +2,7 @@ use glm::{vec3, Vec3};
 
 use super::{
     axis::Axis,
+    orientation::Orientation,
     sides::*,
 };
 
@@ -76,6 +77,40 @@ impl ShellTransform for Vec3 {
     }
 }
 
+impl ShellTransform for Side {
+    fn flip(&mut self, axis: Axis) -> &mut Self {
+        *self = Orientation::from_action(Flip(axis)).side(*self);
+        self
+    }
+
+    fn turn_counter_clockwise(&mut self, axis: Axis) -> &mut Self {
+        *self = Orientation::from_action(TurnCounterClockwise(axis)).side(*self);
+        self
+    }
+
+    fn turn_clockwise(&mut self, axis: Axis) -> &mut Self {
+        *self = Orientation::from_action(TurnClockwise(axis)).side(*self);
+        self
+    }
+}
+
+impl ShellTransform for Sides {
+    fn flip(&mut self, axis: Axis) -> &mut Self {
+        *self = Orientation::from_action(Flip(axis)).sides(*self);
+        self
+    }
+
+    fn turn_counter_clockwise(&mut self, axis: Axis) -> &mut Self {
+        *self = Orientation::from_action(TurnCounterClockwise(axis)).sides(*self);
+        self
+    }
+
+    fn turn_clockwise(&mut self, axis: Axis) -> &mut Self {
+        *self = Orientation::from_action(TurnClockwise(axis)).sides(*self);
+        self
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct Shell {
     front: Side,
@@ -106,6 +141,13 @@ impl Shell {
             Right => self.right,
         }
     }
+
+    pub fn transform_sides(&self, local: Sides) -> Sides {
+        Side::ALL
+            .iter()
+            .filter(|&&side| local.contains(self.local_side(side)))
+            .fold(Sides::empty(), |res, &side| res | side)
+    }
 }
 
 impl ShellTransform for Shell {
@@ -207,6 +249,14 @@ pub enum ShellTransformAction {
 
 use ShellTransformAction::*;
 
+impl ShellTransformAction {
+    pub fn axis(self) -> Axis {
+        match self {
+            Flip(axis) | TurnCounterClockwise(axis) | TurnClockwise(axis) => axis,
+        }
+    }
+}
+
 impl std::convert::TryFrom<&str> for ShellTransformAction {
     type Error = ();
 
@@ -254,6 +304,47 @@ mod tests {
         assert_eq!(v, vec3(-1., 1., 0.));
     }
 
+    #[test]
+    fn side_conventions() {
+        use super::super::point::Point;
+
+        let actions: Vec<_> = Axis::ALL
+            .iter()
+            .flat_map(|&axis| vec![Flip(axis), TurnCounterClockwise(axis), TurnClockwise(axis)])
+            .collect();
+
+        for &side in &Side::ALL {
+            let Point(x, y, z) = side.into();
+            assert_eq!(vec3(x as f32, y as f32, z as f32), side.normal());
+
+            for &first in &actions {
+                for &second in &actions {
+                    let mut normal = side.normal();
+                    apply_actions(&mut normal, vec![first, second]);
+
+                    let mut moved = side;
+                    apply_actions(&mut moved, vec![first, second]);
+                    assert_eq!(moved.normal(), normal, "{} after {} and {}", side, first, second);
+                    assert_eq!(Side::from_normal(normal), Some(moved));
+
+                    let mut shell = Shell::new();
+                    apply_actions(&mut shell, vec![first, second]);
+                    assert_eq!(shell.local_side(moved), side);
+                }
+
+                let mut moved = side;
+                apply_action(&mut moved, first);
+
+                match first {
+                    Flip(axis) if axis == side.axis() => assert_eq!(moved, side.opposite()),
+                    Flip(_) => assert_eq!(moved, side),
+                    _ if first.axis() == side.axis() => assert_eq!(moved, side),
+                    _ => assert_ne!(moved.axis(), side.axis()),
+                }
+            }
+        }
+    }
+
     #[test]
     fn flip_shell() {
         let mut s = Shell::new();
@@ -346,6 +437,29 @@ mod tests {
         assert_eq!(s, *Shell::new().turn_clockwise(Axis::Z));
     }
 
+    #[test]
+    fn transform_sides() {
+        let actions = [TurnCounterClockwise(Axis::Y), Flip(Axis::Z), TurnClockwise(Axis::X)];
+
+        let mut shell = Shell::new();
+        apply_actions(&mut shell, actions.iter().copied());
+
+        for bits in 0..64u8 {
+            let local = Sides::from(bits);
+
+            let mut sides = local;
+            apply_actions(&mut sides, actions.iter().copied());
+            assert_eq!(shell.transform_sides(local), sides);
+            assert_eq!(sides.len(), local.len());
+        }
+
+        let mut side = Front;
+        side.turn_counter_clockwise(Axis::Y);
+        assert_eq!(side, Left);
+        assert_eq!(shell.transform_sides(Front | Up), *(Front | Up).turn_counter_clockwise(Axis::Y).flip(Axis::Z).turn_clockwise(Axis::X));
+        assert_eq!(shell.transform_sides(Sides::all()), Sides::all());
+    }
+
     #[test]
     fn display() {
         let a = format!("{}", Flip(Axis::X));
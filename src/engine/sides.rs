@@ -1,5 +1,16 @@
 use std::convert::TryInto;
 
+use glm::{vec3, Vec3};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    axis::Axis,
+    super::error::sides::SidesError,
+};
+
+// Block space convention shared by sides, points, normals and shell transforms:
+// Front = +Z, Back = -Z, Up = +Y, Down = -Y, Left = +X, Right = -X.
+// The bit of each side is its position in `Side::ALL`, so Front = 0x01 ... Right = 0x20
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Side {
     Front,
@@ -13,6 +24,69 @@ pub enum Side {
 pub use Side::*;
 
 impl Side {
+    pub const ALL: [Side; 6] = [Front, Back, Up, Down, Left, Right];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Front => "front",
+            Back => "back",
+            Up => "up",
+            Down => "down",
+            Left => "left",
+            Right => "right",
+        }
+    }
+
+    pub fn axis(self) -> Axis {
+        match self {
+            Front | Back => Axis::Z,
+            Up | Down => Axis::Y,
+            Left | Right => Axis::X,
+        }
+    }
+
+    pub fn is_positive(self) -> bool { matches!(self, Front | Up | Left) }
+
+    pub fn from_axis(axis: Axis, positive: bool) -> Self {
+        match (axis, positive) {
+            (Axis::X, true) => Left,
+            (Axis::X, false) => Right,
+            (Axis::Y, true) => Up,
+            (Axis::Y, false) => Down,
+            (Axis::Z, true) => Front,
+            (Axis::Z, false) => Back,
+        }
+    }
+
+    pub fn normal(self) -> Vec3 {
+        let sign = if self.is_positive() { 1. } else { -1. };
+
+        match self.axis() {
+            Axis::X => vec3(sign, 0., 0.),
+            Axis::Y => vec3(0., sign, 0.),
+            Axis::Z => vec3(0., 0., sign),
+        }
+    }
+
+    // Side whose normal is closest to `v`, ties go to X, then Y
+    pub fn from_normal(v: Vec3) -> Option<Self> {
+        let abs = v.abs();
+
+        if !(abs.x.is_finite() && abs.y.is_finite() && abs.z.is_finite()) || abs.max() == 0. {
+            return None;
+        }
+
+        let side = if abs.x >= abs.y && abs.x >= abs.z {
+            Self::from_axis(Axis::X, v.x > 0.)
+        } else if abs.y >= abs.z {
+            Self::from_axis(Axis::Y, v.y > 0.)
+        } else {
+            Self::from_axis(Axis::Z, v.z > 0.)
+        };
+
+        Some(side)
+    }
+
     pub fn opposite(self) -> Self {
         match self {
             Front => Back,
@@ -29,14 +103,23 @@ impl std::convert::TryFrom<u8> for Side {
     type Error = ();
 
     fn try_from(val: u8) -> Result<Self, Self::Error> {
-        match val {
-            0x01 => Ok(Back),
-            0x02 => Ok(Front),
-            0x04 => Ok(Down),
-            0x08 => Ok(Up),
-            0x10 => Ok(Right),
-            0x20 => Ok(Left),
-            _ => Err(()),
+        Side::ALL
+            .iter()
+            .copied()
+            .find(|&side| u8::from(side) == val)
+            .ok_or(())
+    }
+}
+
+impl From<Side> for u8 {
+    fn from(side: Side) -> Self {
+        match side {
+            Front => 0x01,
+            Back => 0x02,
+            Up => 0x04,
+            Down => 0x08,
+            Left => 0x10,
+            Right => 0x20,
         }
     }
 }
@@ -70,6 +153,46 @@ impl From<Side> for char {
     }
 }
 
+impl std::fmt::Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.write_str(self.name()) }
+}
+
+impl std::str::FromStr for Side {
+    type Err = SidesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use std::convert::TryFrom;
+
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Side::try_from(c).map_err(|_| SidesError::UnknownSide(s.to_owned())),
+            _ => Side::ALL
+                .iter()
+                .copied()
+                .find(|side| side.name() == s)
+                .ok_or_else(|| SidesError::UnknownSide(s.to_owned())),
+        }
+    }
+}
+
+impl Serialize for Side {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    { serializer.serialize_str(self.name()) }
+}
+
+impl<'de> Deserialize<'de> for Side {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct Sides {
     bits: u8,
@@ -86,6 +209,10 @@ impl Sides {
     pub fn empty() -> Self { Sides { bits: 0 } }
 
     pub fn all() -> Self { Sides { bits: !0 } }
+
+    pub fn len(self) -> usize { (self.bits & 0b111111).count_ones() as usize }
+
+    pub fn is_empty(self) -> bool { self.len() == 0 }
 }
 
 impl PartialEq for Sides {
@@ -110,6 +237,63 @@ impl Default for Sides {
     fn default() -> Self { Sides { bits: 0 } }
 }
 
+impl std::fmt::Display for Sides {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::fmt::Write;
+
+        if *self == Sides::all() {
+            return f.write_char('.');
+        }
+
+        for &s in Side::ALL.iter().filter(|&&s| self.contains(s)) {
+            f.write_char(s.into())?
+        }
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Sides {
+    type Err = SidesError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        use std::convert::TryFrom;
+
+        if src == "." {
+            return Ok(Sides::all());
+        }
+
+        src
+            .chars()
+            .try_fold(Sides::empty(), |res, c| {
+                let side = Side::try_from(c).map_err(|_| SidesError::UnknownSide(c.to_string()))?;
+
+                if res.contains(side) {
+                    Err(SidesError::DuplicateSide(c))
+                } else {
+                    Ok(res | side)
+                }
+            })
+    }
+}
+
+impl Serialize for Sides {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    { serializer.serialize_str(&self.to_string()) }
+}
+
+impl<'de> Deserialize<'de> for Sides {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
 impl From<u8> for Sides {
     fn from(val: u8) -> Self { Sides { bits: val } }
 }
@@ -129,19 +313,8 @@ impl From<&str> for Sides {
     }
 }
 
-impl Into<Sides> for Side {
-    fn into(self) -> Sides {
-        let sides = match self {
-            Front => 0x01,
-            Back => 0x02,
-            Up => 0x04,
-            Down => 0x08,
-            Left => 0x10,
-            Right => 0x20,
-        };
-
-        Sides { bits: sides }
-    }
+impl From<Side> for Sides {
+    fn from(side: Side) -> Self { Sides { bits: side.into() } }
 }
 
 impl std::ops::BitOr<Side> for Side {
@@ -301,6 +474,85 @@ mod tests {
         assert_eq!(s, Sides::all());
     }
 
+    #[test]
+    fn display_and_parse() {
+        assert_eq!((Up | Front | Right).to_string(), "fur");
+        assert_eq!(Sides::empty().to_string(), "");
+        assert_eq!(Sides::all().to_string(), ".");
+        assert_eq!(Sides::from(0b111111).to_string(), ".");
+
+        for bits in 0..64u8 {
+            let sides = Sides::from(bits);
+            assert_eq!(sides.to_string().parse::<Sides>(), Ok(sides));
+            assert_eq!(sides.len(), bits.count_ones() as usize);
+        }
+
+        assert_eq!("rlu".parse::<Sides>(), Ok(Right | Left | Up));
+        assert_eq!("".parse::<Sides>(), Ok(Sides::empty()));
+        assert_eq!("fq".parse::<Sides>(), Err(SidesError::UnknownSide("q".to_owned())));
+        assert_eq!("udu".parse::<Sides>(), Err(SidesError::DuplicateSide('u')));
+
+        assert!(Sides::empty().is_empty());
+        assert_eq!(Sides::all().len(), 6);
+
+        assert_eq!(Left.to_string(), "left");
+        assert_eq!("left".parse::<Side>(), Ok(Left));
+        assert_eq!("L".parse::<Side>(), Ok(Left));
+        assert_eq!("lr".parse::<Side>(), Err(SidesError::UnknownSide("lr".to_owned())));
+    }
+
+    #[test]
+    fn serde() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Config {
+            facing: Side,
+            solid: Sides,
+        }
+
+        let config: Config = serde_yaml::from_str("{ facing: back, solid: ud }").unwrap();
+        assert_eq!(config, Config { facing: Back, solid: Up | Down });
+
+        let code = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(serde_yaml::from_str::<Config>(&code).unwrap(), config);
+
+        assert!(serde_yaml::from_str::<Config>("{ facing: b, solid: udx }").is_err());
+        assert!(serde_yaml::from_str::<Config>("{ facing: top, solid: . }").is_err());
+    }
+
+    #[test]
+    fn bits() {
+        use std::convert::TryFrom;
+
+        for (i, &side) in Side::ALL.iter().enumerate() {
+            assert_eq!(u8::from(side), 1 << i);
+            assert_eq!(Side::try_from(u8::from(side)), Ok(side));
+            assert_eq!(Sides::from(side), Sides::from(1 << i));
+        }
+
+        assert_eq!(Side::try_from(0x03), Err(()));
+        assert_eq!(Sides::all().into_iter().collect::<Vec<_>>(), Side::ALL);
+        assert_eq!((Right | Up | Back).into_iter().collect::<Vec<_>>(), [Back, Up, Right]);
+    }
+
+    #[test]
+    fn axis_and_normal() {
+        for &side in &Side::ALL {
+            assert_eq!(Side::from_axis(side.axis(), side.is_positive()), side);
+            assert_eq!(side.opposite().axis(), side.axis());
+            assert_ne!(side.opposite().is_positive(), side.is_positive());
+            assert_eq!(side.opposite().normal(), -side.normal());
+            assert_eq!(Side::from_normal(side.normal()), Some(side));
+            assert_eq!(Side::from_normal(side.normal() * 3. + vec3(0.5, -0.5, 0.5)), Some(side));
+        }
+
+        assert_eq!(Left.normal(), vec3(1., 0., 0.));
+        assert_eq!(Front.normal(), vec3(0., 0., 1.));
+        assert_eq!(Side::from_normal(vec3(1., -1., 0.)), Some(Left));
+        assert_eq!(Side::from_normal(vec3(0., -1., 1.)), Some(Down));
+        assert_eq!(Side::from_normal(vec3(0., 0., 0.)), None);
+        assert_eq!(Side::from_normal(vec3(f32::NAN, 0., 1.)), None);
+    }
+
     #[test]
     fn debug() {
         let bdr: Sides = 0b101010.into();
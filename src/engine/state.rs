@@ -1,8 +1,46 @@
-use super::shell_transform::Shell;
+use std::fmt;
 
-#[derive(Debug, PartialEq)]
-pub struct State {
-    pub model: usize,
+use super::{
+    handle::Handle,
+    shell_transform::Shell,
+    sides::Side,
+};
+
+pub struct State<M, T> {
+    pub model: Handle<M>,
     pub shell: Shell,
-    pub layers: Vec<u32>,
+    pub layers: Vec<Handle<T>>,
+    pub facing: Side,
+}
+
+// Handles are plain indices, so these don't need any bounds on `M` and `T`
+impl<M, T> Clone for State<M, T> {
+    fn clone(&self) -> Self {
+        State {
+            model: self.model,
+            shell: self.shell,
+            layers: self.layers.clone(),
+            facing: self.facing,
+        }
+    }
+}
+
+impl<M, T> PartialEq for State<M, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.model == other.model
+            && self.shell == other.shell
+            && self.layers == other.layers
+            && self.facing == other.facing
+    }
+}
+
+impl<M, T> fmt::Debug for State<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("State")
+            .field("model", &self.model)
+            .field("shell", &self.shell)
+            .field("layers", &self.layers)
+            .field("facing", &self.facing)
+            .finish()
+    }
 }
@@ -0,0 +1,298 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    handle::Handle,
+    resource::Resource,
+    super::{
+        asset::Asset,
+        error::asset::PathError,
+        load::Load,
+    },
+};
+
+type Loaded<T> = (Resource<T>, Vec<(String, <T as Load>::Error)>);
+type Joined<T> = (Resource<T>, Vec<(String, <T as Load>::Error)>, <T as Load>::Loader);
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Progress {
+    pub loaded: usize,
+    pub total: usize,
+    // The file the next step loads, None once the queue is empty
+    pub next: Option<String>,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.
+        } else {
+            self.loaded as f32 / self.total as f32
+        }
+    }
+
+    pub fn is_done(&self) -> bool { self.loaded >= self.total }
+}
+
+#[derive(Debug)]
+pub struct StreamingLoader<T>
+    where
+        T: Load,
+{
+    queue: VecDeque<String>,
+    resource: Resource<T>,
+    failed: Vec<(String, T::Error)>,
+    loaded: usize,
+    total: usize,
+}
+
+impl<T> StreamingLoader<T>
+    where
+        T: Load,
+{
+    pub fn new() -> Self { Self::with_resource(Resource::new()) }
+
+    pub fn with_resource(resource: Resource<T>) -> Self {
+        StreamingLoader {
+            queue: VecDeque::new(),
+            resource,
+            failed: Vec::new(),
+            loaded: 0,
+            total: 0,
+        }
+    }
+
+    pub fn push<S>(&mut self, file: S) -> &mut Self
+        where
+            S: Into<String>,
+    {
+        self.queue.push_back(file.into());
+        self.total += 1;
+        self
+    }
+
+    pub fn push_asset<S>(&mut self, file: S) -> Result<&mut Self, PathError>
+        where
+            S: AsRef<str>,
+            T: Asset,
+    {
+        let path = self.resource.root().locate::<T, _>(file)?;
+        Ok(self.push(path))
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            loaded: self.loaded,
+            total: self.total,
+            next: self.queue.front().cloned(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool { self.queue.is_empty() }
+
+    pub fn step(&mut self, loader: &mut T::Loader) -> Option<Result<Handle<T>, &T::Error>> {
+        let file = self.queue.pop_front()?;
+        self.loaded += 1;
+
+        match self.resource.load_with(file.as_str(), loader) {
+            Ok((handle, _)) => Some(Ok(handle)),
+            Err(e) => {
+                self.failed.push((file, e));
+                self.failed.last().map(|(_, e)| Err(e))
+            }
+        }
+    }
+
+    pub fn pump_count(&mut self, loader: &mut T::Loader, count: usize) -> Progress {
+        for _ in 0..count {
+            if self.step(loader).is_none() {
+                break;
+            }
+        }
+
+        self.progress()
+    }
+
+    pub fn pump(&mut self, loader: &mut T::Loader, budget: Duration) -> Progress {
+        let start = Instant::now();
+
+        while self.step(loader).is_some() && start.elapsed() < budget {}
+
+        self.progress()
+    }
+
+    pub fn resource(&self) -> &Resource<T> { &self.resource }
+
+    pub fn failed(&self) -> &[(String, T::Error)] { &self.failed }
+
+    pub fn finish(self) -> Loaded<T> { (self.resource, self.failed) }
+
+    pub fn spawn(mut self, mut loader: T::Loader) -> Worker<T>
+        where
+            T: Send + 'static,
+            T::Loader: Send + 'static,
+            T::Error: Send + 'static,
+    {
+        let progress = Arc::new(Mutex::new(self.progress()));
+        let shared = Arc::clone(&progress);
+
+        let thread = thread::spawn(move || {
+            while self.step(&mut loader).is_some() {
+                *shared.lock().unwrap_or_else(|e| e.into_inner()) = self.progress();
+            }
+
+            let (resource, failed) = self.finish();
+            (resource, failed, loader)
+        });
+
+        Worker { progress, thread }
+    }
+}
+
+impl<T> Default for StreamingLoader<T>
+    where
+        T: Load,
+{
+    fn default() -> Self { StreamingLoader::new() }
+}
+
+#[derive(Debug)]
+pub struct Worker<T>
+    where
+        T: Load,
+{
+    progress: Arc<Mutex<Progress>>,
+    thread: thread::JoinHandle<Joined<T>>,
+}
+
+impl<T> Worker<T>
+    where
+        T: Load,
+{
+    pub fn progress(&self) -> Progress { self.progress.lock().unwrap_or_else(|e| e.into_inner()).clone() }
+
+    pub fn is_finished(&self) -> bool { self.thread.is_finished() }
+
+    // The loader comes back as well, for tiles it owns the models and textures their handles point into
+    pub fn join(self) -> thread::Result<Joined<T>> { self.thread.join() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Tile(String);
+
+    impl Load for Tile {
+        type Error = String;
+        type Loader = usize;
+
+        fn load<S>(file: S, calls: &mut Self::Loader) -> Result<Self, Self::Error>
+            where
+                S: AsRef<str>,
+        {
+            *calls += 1;
+
+            match file.as_ref() {
+                f if f.starts_with("bad") => Err(f.to_owned()),
+                f => Ok(Tile(f.to_owned())),
+            }
+        }
+    }
+
+    impl Asset for Tile {
+        const DIR: &'static str = "tiles";
+    }
+
+    fn loader() -> StreamingLoader<Tile> {
+        let mut loader = StreamingLoader::new();
+        loader.push("one").push("bad").push("two").push("one");
+        loader
+    }
+
+    #[test]
+    fn pump() {
+        let mut stream = loader();
+        let mut calls = 0;
+
+        assert_eq!(stream.progress(), Progress { loaded: 0, total: 4, next: Some("one".to_owned()) });
+
+        let progress = stream.pump_count(&mut calls, 2);
+        assert_eq!(progress, Progress { loaded: 2, total: 4, next: Some("two".to_owned()) });
+        assert_eq!(progress.fraction(), 0.5);
+        assert_eq!(stream.failed().len(), 1);
+
+        assert!(stream.step(&mut calls).unwrap().is_ok());
+        let progress = stream.pump(&mut calls, Duration::from_secs(1));
+        assert!(progress.is_done());
+        assert!(progress.next.is_none());
+        assert!(stream.step(&mut calls).is_none());
+        assert_eq!(calls, 3);
+
+        stream.push_asset("three").unwrap();
+        assert!(stream.push_asset("../three").is_err());
+        assert!(!stream.is_done());
+        stream.pump_count(&mut calls, 10);
+
+        let (res, failed) = stream.finish();
+        assert_eq!(res.len(), 3);
+        assert!(res.get(Tile::full_path("three").unwrap()).is_some());
+        assert_eq!(failed, [("bad".to_owned(), "bad".to_owned())]);
+    }
+
+    #[test]
+    fn worker() {
+        let worker = loader().spawn(0);
+        let (res, failed, calls) = worker.join().unwrap();
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(res.get("two").unwrap().0, "two");
+        assert_eq!(failed.len(), 1);
+        assert_eq!(calls, 3);
+
+        let worker = StreamingLoader::<Tile>::new().spawn(0);
+        assert!(worker.progress().is_done());
+        assert_eq!(worker.join().unwrap().0.len(), 0);
+    }
+
+    #[test]
+    fn worker_keeps_tile_resources() {
+        use crate::engine::{model::Symmetry, resource::Resource, tile::Tile};
+
+        struct Model;
+
+        impl Symmetry for Model {}
+
+        impl Load for Model {
+            type Error = ();
+            type Loader = ();
+
+            fn load<S>(_: S, _: &mut Self::Loader) -> Result<Self, Self::Error>
+                where
+                    S: AsRef<str>,
+            { Ok(Model) }
+        }
+
+        let dir = std::env::temp_dir().join(format!("rt_tools_worker_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("tile.yml").to_string_lossy().into_owned();
+        std::fs::write(&file, "{ models: [ m1 ], textures: [ t1 ], states: [ { model: 0, layers: [ 0 ] } ] }").unwrap();
+
+        let mut stream = StreamingLoader::<Tile<Model, Model>>::new();
+        stream.push(file.as_str());
+
+        let (tiles, failed, (models, textures)) = stream.spawn((Resource::new(), Resource::new())).join().unwrap();
+        assert!(failed.is_empty());
+
+        let state = &tiles.get(file.as_str()).unwrap().states[0];
+        assert!(models.is_valid(state.model));
+        assert!(textures.is_valid(state.layers[0]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
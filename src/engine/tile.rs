@@ -1,11 +1,93 @@
-use super::state::State;
+use std::fmt;
 
-#[derive(Debug, PartialEq)]
-pub struct Tile {
-    pub states: Vec<State>,
+use super::{
+    axis::Axis,
+    handle::Handle,
+    orientation::{Orientation, Rotations},
+    placement::Placement,
+    resource::Resource,
+    sides::Side,
+    state::State,
+};
+
+pub struct Tile<M, T> {
+    pub states: Vec<State<M, T>>,
     pub id: u32,
 }
 
-impl Tile {
-    pub fn detect_state(&self) -> &State { &self.states[0] }
+impl<M, T> Clone for Tile<M, T> {
+    fn clone(&self) -> Self { Tile { states: self.states.clone(), id: self.id } }
+}
+
+impl<M, T> PartialEq for Tile<M, T> {
+    fn eq(&self, other: &Self) -> bool { self.states == other.states && self.id == other.id }
+}
+
+impl<M, T> fmt::Debug for Tile<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tile")
+            .field("states", &self.states)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<M, T> Tile<M, T> {
+    pub fn detect_state(&self) -> &State<M, T> { &self.states[0] }
+
+    // The state facing exactly `side`, or None when the tile has no such variant.
+    // Tiles expanded with `axis` have one state per axis, look those up with `state_along`
+    pub fn state_facing(&self, side: Side) -> Option<&State<M, T>> { self.states.iter().find(|s| s.facing == side) }
+
+    pub fn state_along(&self, axis: Axis) -> Option<&State<M, T>> { self.states.iter().find(|s| s.facing.axis() == axis) }
+
+    pub fn state_oriented(&self, facing: Side, up: Side) -> Option<&State<M, T>> {
+        self.states
+            .iter()
+            .find(|s| s.facing == facing && Orientation::from(s.shell).side(Side::Up) == up)
+    }
+
+    pub fn place(&self, rotations: Rotations, placement: &Placement) -> Option<&State<M, T>> {
+        let (facing, up) = placement.facing(rotations);
+
+        match rotations {
+            Rotations::Axis => self.state_along(facing.axis()),
+            _ => self.state_oriented(facing, up).or_else(|| self.state_facing(facing)),
+        }
+    }
+
+    pub fn models(&self) -> Vec<Handle<M>> {
+        let mut models = Vec::new();
+
+        for state in &self.states {
+            if !models.contains(&state.model) {
+                models.push(state.model);
+            }
+        }
+
+        models
+    }
+
+    pub fn textures(&self) -> Vec<Handle<T>> {
+        let mut textures = Vec::new();
+
+        for &layer in self.states.iter().flat_map(|s| &s.layers) {
+            if !textures.contains(&layer) {
+                textures.push(layer);
+            }
+        }
+
+        textures
+    }
+
+    // A tile holds one reference to each model and texture it uses
+    pub fn retain(&self, models: &mut Resource<M>, textures: &mut Resource<T>) {
+        self.models().into_iter().for_each(|m| { models.retain(m); });
+        self.textures().into_iter().for_each(|t| { textures.retain(t); });
+    }
+
+    pub fn release(&self, models: &mut Resource<M>, textures: &mut Resource<T>) {
+        self.models().into_iter().for_each(|m| { models.release(m); });
+        self.textures().into_iter().for_each(|t| { textures.release(t); });
+    }
 }
@@ -0,0 +1,123 @@
+use glm::{vec3, Mat4, Vec3};
+
+use super::{
+    orientation::Orientation,
+    shell_transform::Shell,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    matrix: Mat4,
+}
+
+impl Transform {
+    pub fn identity() -> Self { Transform { matrix: Mat4::identity() } }
+
+    pub fn from_matrix(matrix: Mat4) -> Self { Transform { matrix } }
+
+    pub fn translate(v: Vec3) -> Self { Transform { matrix: glm::translation(&v) } }
+
+    pub fn scale(v: Vec3, pivot: Vec3) -> Self { Self::around(glm::scaling(&v), pivot) }
+
+    pub fn rotate(degrees: f32, axis: Vec3, pivot: Vec3) -> Option<Self> {
+        if axis.norm() < f32::EPSILON {
+            return None;
+        }
+
+        Some(Self::around(glm::rotation(degrees.to_radians(), &axis), pivot))
+    }
+
+    fn around(matrix: Mat4, pivot: Vec3) -> Self {
+        Transform { matrix: glm::translation(&pivot) * matrix * glm::translation(&-pivot) }
+    }
+
+    pub fn matrix(&self) -> &Mat4 { &self.matrix }
+
+    pub fn then(self, next: Self) -> Self { Transform { matrix: next.matrix * self.matrix } }
+
+    pub fn is_mirror(&self) -> bool { glm::determinant(&glm::mat4_to_mat3(&self.matrix)) < 0. }
+
+    pub fn apply_point(&self, v: Vec3) -> Vec3 {
+        let res = self.matrix * glm::vec4(v.x, v.y, v.z, 1.);
+        vec3(res.x, res.y, res.z)
+    }
+
+    pub fn apply_normal(&self, v: Vec3) -> Vec3 {
+        let normal = glm::inverse_transpose(glm::mat4_to_mat3(&self.matrix)) * v;
+        glm::normalize(&normal)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self { Self::identity() }
+}
+
+impl From<Orientation> for Transform {
+    fn from(orientation: Orientation) -> Self {
+        let m = orientation.matrix();
+        let mut matrix = Mat4::identity();
+
+        for (i, row) in m.iter().enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                matrix[(i, j)] = v as f32;
+            }
+        }
+
+        Self::around(matrix, vec3(0.5, 0.5, 0.5))
+    }
+}
+
+impl From<Shell> for Transform {
+    fn from(shell: Shell) -> Self { Orientation::from(shell).into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        axis::Axis,
+        shell_transform::ShellTransform,
+    };
+
+    fn assert_near(a: Vec3, b: Vec3) { assert!((a - b).norm() < 1e-5, "{:?} != {:?}", a, b) }
+
+    #[test]
+    fn transform() {
+        let center = vec3(0.5, 0.5, 0.5);
+
+        let t = Transform::translate(vec3(0., 0.5, 0.));
+        assert_near(t.apply_point(vec3(1., 0., 0.)), vec3(1., 0.5, 0.));
+        assert_near(t.apply_normal(vec3(1., 0., 0.)), vec3(1., 0., 0.));
+
+        let t = Transform::scale(vec3(0.5, 0.5, 0.5), center);
+        assert_near(t.apply_point(vec3(1., 1., 1.)), vec3(0.75, 0.75, 0.75));
+        assert!(!t.is_mirror());
+
+        let t = Transform::rotate(90., vec3(0., 1., 0.), center).unwrap();
+        assert_near(t.apply_point(vec3(1., 0., 0.5)), vec3(0.5, 0., 0.));
+        assert_near(t.apply_normal(vec3(1., 0., 0.)), vec3(0., 0., -1.));
+        assert!(Transform::rotate(90., vec3(0., 0., 0.), center).is_none());
+
+        let t = Transform::scale(vec3(1., 0.5, 1.), vec3(0., 0., 0.));
+        assert_near(t.apply_normal(vec3(0., 1., 1.)), glm::normalize(&vec3(0., 2., 1.)));
+
+        let t = Transform::translate(vec3(1., 0., 0.)).then(Transform::scale(vec3(2., 2., 2.), vec3(0., 0., 0.)));
+        assert_near(t.apply_point(vec3(0., 0., 0.)), vec3(2., 0., 0.));
+        assert!(Transform::scale(vec3(-1., 1., 1.), center).is_mirror());
+    }
+
+    #[test]
+    fn shell() {
+        let mut shell = Shell::new();
+        shell.turn_counter_clockwise(Axis::Y).flip(Axis::X);
+
+        let t = Transform::from(shell);
+        let mut v = vec3(0.2, 0.7, 1.);
+        let p = t.apply_point(v);
+
+        v -= vec3(0.5, 0.5, 0.5);
+        v.turn_counter_clockwise(Axis::Y).flip(Axis::X);
+        assert_near(p, v + vec3(0.5, 0.5, 0.5));
+        assert!(t.is_mirror());
+    }
+}
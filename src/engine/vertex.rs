@@ -1,3 +1,9 @@
+use super::{
+    face::Face,
+    normal::calc_tangent,
+    transform::Transform,
+};
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Vertex {
@@ -5,3 +11,87 @@ pub struct Vertex {
     pub st: glm::Vec2,
     pub norm: glm::Vec3,
 }
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExtendedVertex {
+    pub pos: glm::Vec3,
+    pub st: glm::Vec2,
+    pub norm: glm::Vec3,
+    pub tangent: glm::Vec4,
+    pub color: glm::Vec4,
+    pub st2: glm::Vec2,
+}
+
+impl Vertex {
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        Vertex {
+            pos: transform.apply_point(self.pos),
+            st: self.st,
+            norm: transform.apply_normal(self.norm),
+        }
+    }
+}
+
+pub fn white() -> glm::Vec4 { glm::vec4(1., 1., 1., 1.) }
+
+pub trait VertexLayout: Sized {
+    fn extend_from_face(face: &Face, vertexes: &mut Vec<Self>);
+
+    // Same result as transforming a copy of the face first
+    fn extend_from_transformed_face(face: &Face, transform: &Transform, vertexes: &mut Vec<Self>);
+}
+
+impl VertexLayout for Vertex {
+    fn extend_from_face(face: &Face, vertexes: &mut Vec<Self>) {
+        vertexes.extend(face.vertexes.as_slice())
+    }
+
+    fn extend_from_transformed_face(face: &Face, transform: &Transform, vertexes: &mut Vec<Self>) {
+        let start = vertexes.len();
+        vertexes.extend(face.vertexes.as_slice().iter().map(|v| v.transformed(transform)));
+
+        if transform.is_mirror() {
+            vertexes[start..].reverse();
+        }
+    }
+}
+
+impl VertexLayout for ExtendedVertex {
+    fn extend_from_face(face: &Face, vertexes: &mut Vec<Self>) {
+        extend_extended(face, face.vertexes.as_slice(), false, vertexes)
+    }
+
+    fn extend_from_transformed_face(face: &Face, transform: &Transform, vertexes: &mut Vec<Self>) {
+        let vs: Vec<Vertex> = face.vertexes.as_slice().iter().map(|v| v.transformed(transform)).collect();
+        extend_extended(face, &vs, transform.is_mirror(), vertexes)
+    }
+}
+
+// A mirrored face is listed back to front, each vertex keeps its own color and st2
+fn extend_extended(face: &Face, vs: &[Vertex], reversed: bool, vertexes: &mut Vec<ExtendedVertex>) {
+    let order: Vec<usize> = match reversed {
+        true => (0..vs.len()).rev().collect(),
+        false => (0..vs.len()).collect(),
+    };
+
+    let positions: Vec<glm::Vec3> = order.iter().map(|&i| vs[i].pos).collect();
+    let sts: Vec<glm::Vec2> = order.iter().map(|&i| vs[i].st).collect();
+
+    vertexes.extend(
+        order
+            .iter()
+            .map(|&i| {
+                let v = &vs[i];
+
+                ExtendedVertex {
+                    pos: v.pos,
+                    st: v.st,
+                    norm: v.norm,
+                    tangent: calc_tangent(&positions, &sts, &v.norm),
+                    color: face.colors.get(i).copied().unwrap_or_else(white),
+                    st2: face.st2.get(i).copied().unwrap_or(v.st),
+                }
+            })
+    )
+}
@@ -0,0 +1,82 @@
+#[derive(Debug, Eq, PartialEq)]
+pub enum PathError {
+    Empty,
+    Absolute,
+    Escape,
+    NotFound(String),
+}
+
+impl super::Error for PathError {
+    fn title() -> &'static str { "Path Error" }
+
+    fn case(&self) -> &str {
+        match self {
+            PathError::Empty => "Empty Path",
+            PathError::Absolute => "Absolute Path",
+            PathError::Escape => "Escape From Asset Root",
+            PathError::NotFound(_) => "Not Found",
+        }
+    }
+
+    fn clarification(&self) -> Option<String> {
+        match self {
+            PathError::NotFound(file) => Some(format!("at {}", file)),
+            _ => None,
+        }
+    }
+
+    fn advice(&self) -> Option<String> {
+        match self {
+            PathError::Absolute | PathError::Escape =>
+                Some("asset paths must be relative to the asset root".to_owned()),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", super::Error::display(self))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AssetError<E> {
+    PathError(PathError),
+    LoadError(E),
+}
+
+impl<E> From<PathError> for AssetError<E> {
+    fn from(err: PathError) -> Self { AssetError::PathError(err) }
+}
+
+impl<E> super::Error for AssetError<E> {
+    fn title() -> &'static str { "Asset Error" }
+
+    fn case(&self) -> &str {
+        match self {
+            AssetError::PathError(e) => e.case(),
+            AssetError::LoadError(_) => "Load Error",
+        }
+    }
+
+    fn clarification(&self) -> Option<String> {
+        match self {
+            AssetError::PathError(e) => e.clarification(),
+            AssetError::LoadError(_) => None,
+        }
+    }
+
+    fn advice(&self) -> Option<String> {
+        match self {
+            AssetError::PathError(e) => e.advice(),
+            AssetError::LoadError(_) => None,
+        }
+    }
+}
+
+impl<E> std::fmt::Display for AssetError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", super::Error::display(self))
+    }
+}
@@ -0,0 +1,41 @@
+use crate::engine::atlas::AtlasError;
+
+#[derive(Debug)]
+pub enum BundleError<E> {
+    TileError(E),
+    TextureError(String, AtlasError),
+}
+
+impl<E> super::Error for BundleError<E> {
+    fn title() -> &'static str { "Bundle Error" }
+
+    fn case(&self) -> &str {
+        match self {
+            BundleError::TileError(_) => "Tile Error",
+            BundleError::TextureError(_, AtlasError::ImageError(_)) => "Texture Error",
+            BundleError::TextureError(_, AtlasError::IncorrectSpriteSize) => "Incorrect Sprite Size",
+        }
+    }
+
+    fn clarification(&self) -> Option<String> {
+        match self {
+            BundleError::TextureError(file, AtlasError::ImageError(e)) => Some(format!("{} at {}", e, file)),
+            BundleError::TextureError(file, _) => Some(format!("at {}", file)),
+            _ => None,
+        }
+    }
+
+    fn advice(&self) -> Option<String> {
+        match self {
+            BundleError::TextureError(_, AtlasError::IncorrectSpriteSize) =>
+                Some("textures must match the sprite size of the manifest".to_owned()),
+            _ => None,
+        }
+    }
+}
+
+impl<E> std::fmt::Display for BundleError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", super::Error::display(self))
+    }
+}
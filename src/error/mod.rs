@@ -1,4 +1,8 @@
+pub mod asset;
+pub mod bundle;
 pub mod model;
+pub mod pack;
+pub mod sides;
 pub mod tile;
 
 pub trait Error {
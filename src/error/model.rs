@@ -1,11 +1,16 @@
+use super::sides::SidesError;
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ModelField {
     Pos,
     St,
     Norm,
+    St2,
+    Color,
     DataPos,
     DataSt,
     DataNorm,
+    DataSt2,
 }
 
 impl ModelField {
@@ -14,9 +19,12 @@ impl ModelField {
             ModelField::Pos => "pos",
             ModelField::St => "st",
             ModelField::Norm => "norm",
+            ModelField::St2 => "st2",
+            ModelField::Color => "color",
             ModelField::DataPos => "data.pos",
             ModelField::DataSt => "data.st",
             ModelField::DataNorm => "data.norm",
+            ModelField::DataSt2 => "data.st2",
         }
     }
 }
@@ -28,6 +36,8 @@ pub enum FaceError {
     ArrayError,
     OutOfRange(ModelField, usize),
     IncorrectDataFormat,
+    ContactError(SidesError),
+    TriangulationError,
 }
 
 impl super::Error for FaceError {
@@ -39,6 +49,8 @@ impl super::Error for FaceError {
             FaceError::ArrayError => "Array Error",
             FaceError::OutOfRange(..) => "Out of Range",
             FaceError::IncorrectDataFormat => "Incorrect Data Format",
+            FaceError::ContactError(e) => e.case(),
+            FaceError::TriangulationError => "Triangulation Error",
         }
     }
 
@@ -46,6 +58,7 @@ impl super::Error for FaceError {
         match self {
             FaceError::WrongVertexNumber(f) => Some(format!("at {}", f.path())),
             FaceError::OutOfRange(f, i) => Some(format!("at {}[{}]", f.path(), i)),
+            FaceError::ContactError(e) => e.clarification().map(|c| format!("in contact {}", c)),
             _ => None,
         }
     }
@@ -56,6 +69,8 @@ pub enum ModelError {
     FacesError,
     FaceError(FaceError),
     ArrayError,
+    TransformError,
+    FullSidesError(SidesError),
 }
 
 impl From<FaceError> for ModelError {
@@ -70,12 +85,23 @@ impl super::Error for ModelError {
             ModelError::FacesError => "Faces Error",
             ModelError::FaceError(fe) => fe.case(),
             ModelError::ArrayError => "Array Error",
+            ModelError::TransformError => "Transform Error",
+            ModelError::FullSidesError(e) => e.case(),
         }
     }
 
     fn clarification(&self) -> Option<String> {
         match self {
             ModelError::FaceError(fe) => fe.clarification(),
+            ModelError::FullSidesError(e) => e.clarification().map(|c| format!("in full_sides {}", c)),
+            _ => None,
+        }
+    }
+
+    fn advice(&self) -> Option<String> {
+        match self {
+            ModelError::FaceError(FaceError::ContactError(e)) | ModelError::FullSidesError(e) => e.advice(),
+            ModelError::FaceError(FaceError::TriangulationError) => Some("The outline of a face must not cross itself".to_string()),
             _ => None,
         }
     }
@@ -0,0 +1,54 @@
+#[derive(Debug)]
+pub enum PackError {
+    IoError(std::io::Error),
+    ManifestError(serde_yaml::Error),
+    DuplicatePack(String),
+    MissingDependency(String, String),
+    DependencyOrder(String, String),
+}
+
+impl From<std::io::Error> for PackError {
+    fn from(err: std::io::Error) -> Self { PackError::IoError(err) }
+}
+
+impl From<serde_yaml::Error> for PackError {
+    fn from(err: serde_yaml::Error) -> Self { PackError::ManifestError(err) }
+}
+
+impl super::Error for PackError {
+    fn title() -> &'static str { "Pack Error" }
+
+    fn case(&self) -> &str {
+        match self {
+            PackError::IoError(_) => "Io Error",
+            PackError::ManifestError(_) => "Manifest Error",
+            PackError::DuplicatePack(_) => "Duplicate Pack",
+            PackError::MissingDependency(..) => "Missing Dependency",
+            PackError::DependencyOrder(..) => "Dependency Order",
+        }
+    }
+
+    fn clarification(&self) -> Option<String> {
+        match self {
+            PackError::IoError(e) => Some(e.to_string()),
+            PackError::ManifestError(e) => Some(e.to_string()),
+            PackError::DuplicatePack(p) => Some(format!("at {}", p)),
+            PackError::MissingDependency(p, d) => Some(format!("{} requires {}", p, d)),
+            PackError::DependencyOrder(p, d) => Some(format!("{} is loaded before {}", p, d)),
+        }
+    }
+
+    fn advice(&self) -> Option<String> {
+        match self {
+            PackError::DependencyOrder(..) =>
+                Some("give the dependency a lower priority".to_owned()),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", super::Error::display(self))
+    }
+}
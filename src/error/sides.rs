@@ -0,0 +1,39 @@
+#[derive(Debug, Eq, PartialEq)]
+pub enum SidesError {
+    UnknownSide(String),
+    DuplicateSide(char),
+}
+
+impl super::Error for SidesError {
+    fn title() -> &'static str { "Sides Error" }
+
+    fn case(&self) -> &str {
+        match self {
+            SidesError::UnknownSide(_) => "Unknown Side",
+            SidesError::DuplicateSide(_) => "Duplicate Side",
+        }
+    }
+
+    fn clarification(&self) -> Option<String> {
+        match self {
+            SidesError::UnknownSide(s) => Some(format!("at '{}'", s)),
+            SidesError::DuplicateSide(c) => Some(format!("at '{}'", c)),
+        }
+    }
+
+    fn advice(&self) -> Option<String> {
+        match self {
+            SidesError::UnknownSide(_) =>
+                Some("use f, b, u, d, l, r or '.' for all sides".to_owned()),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SidesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", super::Error::display(self))
+    }
+}
+
+impl std::error::Error for SidesError {}
@@ -20,6 +20,7 @@ pub enum StateError<M, T> {
     ModelError(M),
     TextureError(T),
     TransformError,
+    RotationsError,
     NoLayerDefined,
     NoModelDefined,
     OutOfRange(TileField, usize),
@@ -33,6 +34,7 @@ impl<M, T> super::Error for StateError<M, T> {
             StateError::ModelError(_) => "Model Error",
             StateError::TextureError(_) => "Texture Error",
             StateError::TransformError => "Transform Error",
+            StateError::RotationsError => "Rotations Error",
             StateError::NoLayerDefined => "No Layer Defined",
             StateError::NoModelDefined => "NoModel Defined",
             StateError::OutOfRange(_, _) => "Out Of Range",
@@ -3,6 +3,9 @@ pub mod engine;
 pub mod error;
 
 pub mod asset;
+pub mod bundle;
 pub mod get;
 pub mod load;
+pub mod overlay;
 pub mod parse;
+pub mod vfs;
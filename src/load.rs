@@ -35,3 +35,80 @@ impl<T> Load for Box<T>
             S: AsRef<str>,
     { Ok(Box::new(T::load(file, loader)?)) }
 }
+
+impl<T> Load for std::sync::Arc<T>
+    where
+        T: Load,
+{
+    type Error = T::Error;
+    type Loader = T::Loader;
+
+    fn load<S>(file: S, loader: &mut Self::Loader) -> Result<Self, Self::Error>
+        where
+            S: AsRef<str>,
+    { Ok(std::sync::Arc::new(T::load(file, loader)?)) }
+}
+
+#[derive(Debug)]
+pub enum ReadError<E> {
+    IoError(std::io::Error),
+    LoadError(E),
+}
+
+impl<E> From<std::io::Error> for ReadError<E> {
+    fn from(err: std::io::Error) -> Self { ReadError::IoError(err) }
+}
+
+pub trait LoadBytes
+    where
+        Self: Sized,
+{
+    type Error;
+    type Loader;
+
+    fn load_bytes(bytes: &[u8], loader: &mut Self::Loader) -> Result<Self, Self::Error>;
+
+    fn load_from<V>(vfs: &V, file: &str, loader: &mut Self::Loader) -> Result<Self, ReadError<Self::Error>>
+        where
+            V: super::vfs::Vfs + ?Sized,
+    {
+        let bytes = vfs.read(file)?;
+        Self::load_bytes(&bytes, loader).map_err(ReadError::LoadError)
+    }
+}
+
+impl<T> LoadBytes for std::rc::Rc<T>
+    where
+        T: LoadBytes,
+{
+    type Error = T::Error;
+    type Loader = T::Loader;
+
+    fn load_bytes(bytes: &[u8], loader: &mut Self::Loader) -> Result<Self, Self::Error> {
+        Ok(std::rc::Rc::new(T::load_bytes(bytes, loader)?))
+    }
+}
+
+impl<T> LoadBytes for Box<T>
+    where
+        T: LoadBytes,
+{
+    type Error = T::Error;
+    type Loader = T::Loader;
+
+    fn load_bytes(bytes: &[u8], loader: &mut Self::Loader) -> Result<Self, Self::Error> {
+        Ok(Box::new(T::load_bytes(bytes, loader)?))
+    }
+}
+
+impl<T> LoadBytes for std::sync::Arc<T>
+    where
+        T: LoadBytes,
+{
+    type Error = T::Error;
+    type Loader = T::Loader;
+
+    fn load_bytes(bytes: &[u8], loader: &mut Self::Loader) -> Result<Self, Self::Error> {
+        Ok(std::sync::Arc::new(T::load_bytes(bytes, loader)?))
+    }
+}
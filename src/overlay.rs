@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    io,
+};
+
+use serde::Deserialize;
+
+use super::{
+    asset::Asset,
+    engine::{handle::Handle, resource::Resource},
+    error::pack::PackError,
+    load::{LoadBytes, ReadError},
+    vfs::{key, Vfs},
+};
+
+pub const MANIFEST: &str = "pack.yml";
+
+pub type FailedFile<T> = (String, ReadError<<T as LoadBytes>::Error>);
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+pub struct AssetPack {
+    manifest: PackManifest,
+    vfs: Box<dyn Vfs>,
+}
+
+impl AssetPack {
+    pub fn new(manifest: PackManifest, vfs: Box<dyn Vfs>) -> Self { AssetPack { manifest, vfs } }
+
+    pub fn open(vfs: Box<dyn Vfs>) -> Result<Self, PackError> {
+        let manifest = serde_yaml::from_slice(&vfs.read(MANIFEST)?)?;
+        Ok(AssetPack::new(manifest, vfs))
+    }
+
+    pub fn manifest(&self) -> &PackManifest { &self.manifest }
+
+    pub fn name(&self) -> &str { &self.manifest.name }
+}
+
+impl std::fmt::Debug for AssetPack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssetPack")
+            .field("manifest", &self.manifest)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct Overlay {
+    packs: Vec<AssetPack>,
+    sources: HashMap<String, usize>,
+    files: Vec<String>,
+}
+
+impl Overlay {
+    pub fn new(mut packs: Vec<AssetPack>) -> Result<Self, PackError> {
+        packs.sort_by(|a, b| a.manifest.priority.cmp(&b.manifest.priority).then_with(|| a.name().cmp(b.name())));
+
+        for (i, pack) in packs.iter().enumerate() {
+            if packs[..i].iter().any(|p| p.name() == pack.name()) {
+                return Err(PackError::DuplicatePack(pack.name().to_owned()));
+            }
+        }
+
+        for (i, pack) in packs.iter().enumerate() {
+            for dep in &pack.manifest.dependencies {
+                let pos = packs
+                    .iter()
+                    .position(|p| p.name() == dep)
+                    .ok_or_else(|| PackError::MissingDependency(pack.name().to_owned(), dep.clone()))?;
+
+                if pos > i {
+                    return Err(PackError::DependencyOrder(pack.name().to_owned(), dep.clone()));
+                }
+            }
+        }
+
+        let mut sources = HashMap::new();
+        let mut files = Vec::new();
+
+        for (i, pack) in packs.iter().enumerate() {
+            for file in pack.vfs.list()? {
+                if file == MANIFEST {
+                    continue;
+                }
+
+                if sources.insert(file.clone(), i).is_none() {
+                    files.push(file);
+                }
+            }
+        }
+
+        Ok(Overlay { packs, sources, files })
+    }
+
+    pub fn packs(&self) -> &[AssetPack] { &self.packs }
+
+    pub fn source(&self, file: &str) -> Option<&PackManifest> {
+        let &idx = self.sources.get(&key(file).ok()?)?;
+        Some(&self.packs[idx].manifest)
+    }
+
+    pub fn report(&self) -> Vec<(&str, &str)> {
+        self.files
+            .iter()
+            .map(|f| (f.as_str(), self.packs[self.sources[f]].name()))
+            .collect()
+    }
+
+    pub fn registry<T>(&self) -> Vec<&str>
+        where
+            T: Asset,
+    {
+        let prefix = format!("{}/", key(T::DIR).unwrap_or_default());
+
+        self.files
+            .iter()
+            .filter(|f| f.starts_with(&prefix))
+            .map(|f| f.as_str())
+            .collect()
+    }
+
+    // Loads every registered asset of `T` from the winning packs, a failing file doesn't stop the rest
+    pub fn load_registry<T>(&self, resource: &mut Resource<T>, loader: &mut <T as LoadBytes>::Loader)
+                            -> (Vec<Handle<T>>, Vec<FailedFile<T>>)
+        where
+            T: Asset + LoadBytes,
+    {
+        let mut loaded = Vec::new();
+        let mut failed = Vec::new();
+
+        for file in self.registry::<T>() {
+            match resource.load_from(self, file, loader) {
+                Ok((handle, _)) => loaded.push(handle),
+                Err(err) => failed.push((file.to_owned(), err)),
+            }
+        }
+
+        (loaded, failed)
+    }
+}
+
+impl Vfs for Overlay {
+    fn read(&self, file: &str) -> io::Result<Vec<u8>> {
+        match self.sources.get(&key(file)?) {
+            Some(&idx) => self.packs[idx].vfs.read(file),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, file.to_owned())),
+        }
+    }
+
+    fn exists(&self, file: &str) -> bool { key(file).is_ok_and(|k| self.sources.contains_key(&k)) }
+
+    fn list(&self) -> io::Result<Vec<String>> { Ok(self.files.clone()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        load::Load,
+        vfs::PakVfs,
+    };
+
+    fn pack(manifest: &str, files: &[(&str, &str)]) -> AssetPack {
+        let mut vfs = PakVfs::default();
+
+        for (name, data) in [(MANIFEST, manifest)].iter().chain(files) {
+            vfs.insert(name, data.as_bytes().to_vec()).unwrap();
+        }
+
+        AssetPack::open(Box::new(vfs)).unwrap()
+    }
+
+    #[derive(Debug)]
+    struct Tile(String);
+
+    impl Load for Tile {
+        type Error = ();
+        type Loader = ();
+
+        fn load<S>(file: S, _: &mut Self::Loader) -> Result<Self, Self::Error>
+            where
+                S: AsRef<str>,
+        { Ok(Tile(file.as_ref().to_owned())) }
+    }
+
+    impl LoadBytes for Tile {
+        type Error = ();
+        type Loader = ();
+
+        fn load_bytes(bytes: &[u8], _: &mut Self::Loader) -> Result<Self, Self::Error> {
+            match bytes {
+                b"broken" => Err(()),
+                _ => Ok(Tile(String::from_utf8_lossy(bytes).into_owned())),
+            }
+        }
+    }
+
+    impl Asset for Tile {
+        const DIR: &'static str = "tiles";
+    }
+
+    fn default_packs() -> Vec<AssetPack> {
+        vec![
+            pack(
+                "{ name: textures_hd, version: '1.0', priority: 10, dependencies: [ base ] }",
+                &[("textures/stone.png", "hd stone")],
+            ),
+            pack(
+                "{ name: base, version: '0.1' }",
+                &[
+                    ("tiles/stone.yml", "base stone"),
+                    ("tiles/dirt.yml", "base dirt"),
+                    ("textures/stone.png", "base stone"),
+                ],
+            ),
+            pack(
+                "{ name: ores, version: '2.3', priority: 5, dependencies: [ base ] }",
+                &[
+                    ("tiles/stone.yml", "ores stone"),
+                    ("tiles/iron.yml", "ores iron"),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn overlay() {
+        let overlay = Overlay::new(default_packs()).unwrap();
+
+        let names: Vec<&str> = overlay.packs().iter().map(|p| p.name()).collect();
+        assert_eq!(names, ["base", "ores", "textures_hd"]);
+
+        assert_eq!(overlay.read_to_string("tiles/stone.yml").unwrap(), "ores stone");
+        assert_eq!(overlay.read_to_string("tiles/dirt.yml").unwrap(), "base dirt");
+        assert_eq!(overlay.read_to_string("./textures/stone.png").unwrap(), "hd stone");
+        assert!(!overlay.exists(MANIFEST));
+
+        assert_eq!(overlay.registry::<Tile>(), ["tiles/dirt.yml", "tiles/stone.yml", "tiles/iron.yml"]);
+        assert_eq!(overlay.source("tiles/iron.yml").unwrap().version, "2.3");
+
+        assert_eq!(overlay.report(), [
+            ("textures/stone.png", "textures_hd"),
+            ("tiles/dirt.yml", "base"),
+            ("tiles/stone.yml", "ores"),
+            ("tiles/iron.yml", "ores"),
+        ]);
+
+        let mut tiles = Resource::<Tile>::new();
+        let (loaded, failed) = overlay.load_registry(&mut tiles, &mut ());
+        assert!(failed.is_empty());
+
+        let texts: Vec<&str> = loaded.iter().map(|&h| tiles.get(h).unwrap().0.as_str()).collect();
+        assert_eq!(texts, ["base dirt", "ores stone", "ores iron"]);
+
+        let mut packs = default_packs();
+        packs.push(pack("{ name: broken, version: '1', priority: 20 }", &[("tiles/dirt.yml", "broken")]));
+        let overlay = Overlay::new(packs).unwrap();
+
+        let (loaded, failed) = overlay.load_registry(&mut Resource::<Tile>::new(), &mut ());
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, "tiles/dirt.yml");
+    }
+
+    #[test]
+    fn equal_priority() {
+        let make = |first, second| {
+            let a = pack(&format!("{{ name: {}, version: '1' }}", first), &[("tiles/a.yml", first)]);
+            let b = pack(&format!("{{ name: {}, version: '1' }}", second), &[("tiles/a.yml", second)]);
+            Overlay::new(vec![a, b]).unwrap()
+        };
+
+        for overlay in &[make("alpha", "beta"), make("beta", "alpha")] {
+            let names: Vec<&str> = overlay.packs().iter().map(|p| p.name()).collect();
+            assert_eq!(names, ["alpha", "beta"]);
+            assert_eq!(overlay.read_to_string("tiles/a.yml").unwrap(), "beta");
+        }
+    }
+
+    #[test]
+    fn dependencies() {
+        let mut packs = default_packs();
+        packs.remove(1);
+
+        let err = Overlay::new(packs).unwrap_err();
+        assert!(matches!(err, PackError::MissingDependency(ref p, ref d) if p == "ores" && d == "base"));
+
+        let mut packs = default_packs();
+        packs.push(pack("{ name: early, version: '1', priority: -1, dependencies: [ ores ] }", &[]));
+
+        let err = Overlay::new(packs).unwrap_err();
+        assert!(matches!(err, PackError::DependencyOrder(ref p, ref d) if p == "early" && d == "ores"));
+
+        let mut packs = default_packs();
+        packs.push(pack("{ name: base, version: '0.2' }", &[]));
+
+        let err = Overlay::new(packs).unwrap_err();
+        assert!(matches!(err, PackError::DuplicatePack(ref p) if p == "base"));
+    }
+}
@@ -7,13 +7,26 @@ pub(crate) struct Model {
     norm: Option<Vec<[f32; 3]>>,
     faces: Option<Vec<Face>>,
     full_sides: Option<String>,
+    smooth_normals: Option<bool>,
+    transform: Option<Vec<TransformStep>>,
+    collision: Option<Vec<Cuboid>>,
+    outline: Option<Vec<Cuboid>>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub(crate) struct Cuboid {
+    from: [f32; 3],
+    to: [f32; 3],
 }
 
 #[derive(Debug, PartialEq, Deserialize)]
 pub(crate) struct Face {
     pos: Option<Vec<[f32; 3]>>,
     st: Option<Vec<[f32; 2]>>,
-    norm: Option<[f32; 3]>,
+    norm: Option<OneOrMany<[f32; 3]>>,
+    st2: Option<Vec<[f32; 2]>>,
+    color: Option<OneOrMany<[f32; 4]>>,
+    tint: Option<u32>,
     layer: Option<u32>,
     contact: Option<String>,
     data: Option<Data>,
@@ -23,53 +36,173 @@ pub(crate) struct Face {
 pub(crate) struct Data {
     pos: Option<Vec<u32>>,
     st: Option<Vec<u32>>,
-    norm: Option<u32>,
+    norm: Option<OneOrMany<u32>>,
+    st2: Option<Vec<u32>>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TransformStep {
+    Translate([f32; 3]),
+    Scale(OneOrMany<f32>),
+    Rotate {
+        angle: f32,
+        axis: [f32; 3],
+        pivot: Option<[f32; 3]>,
+    },
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
 }
 
 use crate::{
     engine::{
+        aabb::Aabb,
         model as md,
         face as fc,
         vertex::Vertex,
-        normal::calc_normal,
+        normal::{calc_normal, smooth_normals},
+        contact::{calc_contact, calc_full_sides},
+        sides::Sides,
+        transform::Transform,
     },
-    error::model::*,
+    error::{model::*, sides::SidesError},
+    load::{Load, LoadBytes, ReadError},
+    parse::ParseError,
 };
 
+const AUTO: &str = "auto";
+
+fn parse_sides<F>(src: Option<String>, auto: F) -> Result<Sides, SidesError>
+    where
+        F: FnOnce() -> Sides,
+{
+    match src.as_deref() {
+        Some(AUTO) => Ok(auto()),
+        Some(s) => s.parse(),
+        None => Ok(Sides::empty()),
+    }
+}
+
+const PIVOT: [f32; 3] = [0.5, 0.5, 0.5];
+
+// A zero scale flattens the model and leaves no inverse to transform its normals with
+fn scale(v: glm::Vec3) -> Result<Transform, ModelError> {
+    if v.iter().any(|s| !s.is_finite() || s.abs() < f32::EPSILON) {
+        return Err(ModelError::TransformError);
+    }
+
+    Ok(Transform::scale(v, PIVOT.into()))
+}
+
+fn parse_transform(steps: Vec<TransformStep>) -> Result<Transform, ModelError> {
+    steps
+        .into_iter()
+        .try_fold(Transform::identity(), |res, step| {
+            let next = match step {
+                TransformStep::Translate(v) => Transform::translate(v.into()),
+                TransformStep::Scale(OneOrMany::One(s)) => scale(glm::vec3(s, s, s))?,
+                TransformStep::Scale(OneOrMany::Many(s)) => match *s {
+                    [x, y, z] => scale(glm::vec3(x, y, z))?,
+                    _ => Err(ModelError::TransformError)?,
+                },
+                TransformStep::Rotate { angle, axis, pivot } => {
+                    Transform::rotate(angle, axis.into(), pivot.unwrap_or(PIVOT).into())
+                        .ok_or(ModelError::TransformError)?
+                }
+            };
+
+            Ok(res.then(next))
+        })
+}
+
 fn convert(src: Model) -> Result<md::Model, ModelError> {
+    let transform = match src.transform {
+        Some(steps) => Some(parse_transform(steps)?),
+        None => None,
+    };
+
     let pos = src.pos.unwrap_or_default();
     let st = src.st.unwrap_or_default();
     let norm = src.norm.unwrap_or_default();
     let faces = src.faces.unwrap_or_default();
-    let full_sides = src.full_sides.unwrap_or_default();
+    let full_sides = src.full_sides;
+    let smooth = src.smooth_normals.unwrap_or_default();
+
+    let cuboids = |boxes: Vec<Cuboid>| -> Vec<Aabb> {
+        boxes
+            .into_iter()
+            .map(|b| Aabb::new(b.from.into(), b.to.into()))
+            .map(|b| transform.as_ref().map_or(b, |t| b.transform(t)))
+            .collect()
+    };
+
+    let collision = src.collision.map(cuboids);
+    let outline = src.outline.map(cuboids);
 
-    let faces_result: Result<Vec<fc::Face>, FaceError> = faces
+    let faces_result: Result<Vec<(fc::Face, bool)>, FaceError> = faces
         .into_iter()
-        .map(|f| {
-            if f.data.is_some() && (f.pos.is_some() || f.st.is_some() || f.norm.is_some()) {
+        .map(|mut f| {
+            if f.data.is_some()
+                && (f.pos.is_some() || f.st.is_some() || f.norm.is_some() || f.st2.is_some()) {
                 Err(FaceError::IncorrectDataFormat)?
             }
 
-            let vertexes: Vec<Vertex> = if let Some(d) = f.data {
+            let st2_ids = f.data.as_mut().and_then(|d| d.st2.take());
+
+            let (vertexes, explicit_norm): (Vec<Vertex>, bool) = if let Some(d) = f.data {
                 let pos_ids = d.pos
                     .ok_or(FaceError::WrongVertexNumber(ModelField::DataPos))?;
 
                 let st_ids = d.st
                     .ok_or(FaceError::WrongVertexNumber(ModelField::DataSt))?;
 
-                if pos_ids.len() != 3 && pos_ids.len() != 4 {
+                if pos_ids.len() < 3 {
                     Err(FaceError::WrongVertexNumber(ModelField::DataPos))?
                 }
 
-                if st_ids.len() != 3 && st_ids.len() != 4 {
+                if st_ids.len() != pos_ids.len() {
                     Err(FaceError::WrongVertexNumber(ModelField::DataSt))?
                 }
 
-                let norm = d
-                    .norm
-                    .and_then(|idx| norm.get(idx as usize))
-                    .map(|&[q, w, e]| Ok(glm::vec3(q, w, e)))
-                    .unwrap_or_else(|| {
+                let norms = match d.norm {
+                    Some(OneOrMany::One(idx)) => {
+                        let idx = idx as usize;
+
+                        let &n = norm
+                            .get(idx)
+                            .ok_or(FaceError::OutOfRange(ModelField::Norm, idx))?;
+
+                        Some(vec![n.into(); pos_ids.len()])
+                    }
+                    Some(OneOrMany::Many(ids)) => {
+                        if ids.len() != pos_ids.len() {
+                            Err(FaceError::WrongVertexNumber(ModelField::DataNorm))?
+                        }
+
+                        let res: Result<Vec<glm::Vec3>, FaceError> = ids
+                            .into_iter()
+                            .map(|idx| idx as usize)
+                            .map(|idx| norm
+                                .get(idx)
+                                .map(|&n| n.into())
+                                .ok_or(FaceError::OutOfRange(ModelField::Norm, idx)))
+                            .collect();
+
+                        Some(res?)
+                    }
+                    None => None,
+                };
+
+                let explicit_norm = norms.is_some();
+
+                let norms = match norms {
+                    Some(norms) => norms,
+                    None => {
                         let i = pos_ids[0] as usize;
                         let j = pos_ids[1] as usize;
                         let k = pos_ids[2] as usize;
@@ -83,14 +216,16 @@ fn convert(src: Model) -> Result<md::Model, ModelError> {
                         let &c = pos.get(k)
                             .ok_or(FaceError::OutOfRange(ModelField::Pos, k))?;
 
-                        Ok(calc_normal(a, b, c))
-                    })?;
+                        vec![calc_normal(a, b, c); pos_ids.len()]
+                    }
+                };
 
                 let res: Result<Vec<Vertex>, FaceError> = pos_ids
                     .into_iter()
                     .zip(st_ids)
-                    .map(|(pos_id, st_id)| ((pos_id as usize, st_id as usize)))
-                    .map(|(pos_id, st_id)| {
+                    .zip(norms)
+                    .map(|((pos_id, st_id), norm)| (pos_id as usize, st_id as usize, norm))
+                    .map(|(pos_id, st_id, norm)| {
                         let &[x, y, z] = pos
                             .get(pos_id)
                             .ok_or(FaceError::OutOfRange(ModelField::Pos, pos_id))?;
@@ -107,7 +242,7 @@ fn convert(src: Model) -> Result<md::Model, ModelError> {
                     })
                     .collect();
 
-                res?
+                (res?, explicit_norm)
             } else {
                 let pos = f.pos
                     .ok_or(FaceError::WrongVertexNumber(ModelField::Pos))?;
@@ -115,34 +250,131 @@ fn convert(src: Model) -> Result<md::Model, ModelError> {
                 let st = f.st
                     .ok_or(FaceError::WrongVertexNumber(ModelField::St))?;
 
-                let norm = f
-                    .norm
-                    .map(|[q, w, e]| glm::vec3(q, w, e))
-                    .unwrap_or_else(|| calc_normal(pos[0], pos[1], pos[2]));
+                if pos.len() < 3 {
+                    Err(FaceError::WrongVertexNumber(ModelField::Pos))?
+                }
+
+                if st.len() != pos.len() {
+                    Err(FaceError::WrongVertexNumber(ModelField::St))?
+                }
+
+                let explicit_norm = f.norm.is_some();
+
+                let norms: Vec<glm::Vec3> = match f.norm {
+                    Some(OneOrMany::One(n)) => vec![n.into(); pos.len()],
+                    Some(OneOrMany::Many(ns)) => {
+                        if ns.len() != pos.len() {
+                            Err(FaceError::WrongVertexNumber(ModelField::Norm))?
+                        }
+
+                        ns.into_iter().map(|n| n.into()).collect()
+                    }
+                    None => vec![calc_normal(pos[0], pos[1], pos[2]); pos.len()],
+                };
 
-                pos
+                let vertexes = pos
                     .into_iter()
                     .zip(st)
-                    .map(|([x, y, z], [s, t])|
+                    .zip(norms)
+                    .map(|(([x, y, z], [s, t]), norm)|
                         Vertex {
                             pos: glm::vec3(x, y, z),
                             st: glm::vec2(s, t),
                             norm,
                         })
-                    .collect()
+                    .collect();
+
+                (vertexes, explicit_norm)
+            };
+
+            let st2: Vec<glm::Vec2> = match (f.st2, st2_ids) {
+                (Some(st2), _) => {
+                    if st2.len() != vertexes.len() {
+                        Err(FaceError::WrongVertexNumber(ModelField::St2))?
+                    }
+
+                    st2.into_iter().map(|[s, t]| glm::vec2(s, t)).collect()
+                }
+                (None, Some(ids)) => {
+                    if ids.len() != vertexes.len() {
+                        Err(FaceError::WrongVertexNumber(ModelField::DataSt2))?
+                    }
+
+                    let res: Result<Vec<glm::Vec2>, FaceError> = ids
+                        .into_iter()
+                        .map(|idx| idx as usize)
+                        .map(|idx| st
+                            .get(idx)
+                            .map(|&[s, t]| glm::vec2(s, t))
+                            .ok_or(FaceError::OutOfRange(ModelField::St2, idx)))
+                        .collect();
+
+                    res?
+                }
+                (None, None) => vec![],
+            };
+
+            let colors: Vec<glm::Vec4> = match f.color {
+                Some(OneOrMany::One(c)) => vec![c.into(); vertexes.len()],
+                Some(OneOrMany::Many(cs)) => {
+                    if cs.len() != vertexes.len() {
+                        Err(FaceError::WrongVertexNumber(ModelField::Color))?
+                    }
+
+                    cs.into_iter().map(|c| c.into()).collect()
+                }
+                None => vec![],
             };
 
-            Ok(fc::Face {
-                vertexes: fc::FaceVertexes::from_slice(&*vertexes).unwrap(),
-                contact: f.contact.unwrap_or_default().as_str().into(),
+            let mut face = fc::Face {
+                contact: Sides::empty(),
+                vertexes: fc::FaceVertexes::from_slice(&vertexes)
+                    .ok_or(FaceError::WrongVertexNumber(ModelField::Pos))?,
                 layer: f.layer.unwrap_or_default(),
-            })
+                colors,
+                st2,
+                tint: f.tint,
+            };
+
+            if let Some(t) = &transform {
+                face.transform(t);
+            }
+
+            if let fc::FaceVertexes::Polygon(vs) = &face.vertexes {
+                let positions: Vec<glm::Vec3> = vs.iter().map(|v| v.pos).collect();
+
+                if fc::triangulate(&positions).is_none() {
+                    Err(FaceError::TriangulationError)?
+                }
+            }
+
+            face.contact = parse_sides(f.contact, || calc_contact(face.vertexes.as_slice().iter().map(|v| &v.pos)))
+                .map_err(FaceError::ContactError)?;
+
+            Ok((face, explicit_norm))
         })
         .collect();
 
+    let mut faces = faces_result?;
+
+    if smooth {
+        smooth_normals(faces
+            .iter_mut()
+            .filter(|(_, explicit_norm)| !explicit_norm)
+            .map(|(face, _)| &mut face.vertexes)
+        );
+    }
+
+    let faces: Vec<fc::Face> = faces
+        .into_iter()
+        .map(|(face, _)| face)
+        .collect();
+
     Ok(md::Model {
-        faces: faces_result?,
-        full_sides: full_sides.as_str().into(),
+        full_sides: parse_sides(full_sides, || calc_full_sides(&faces)).map_err(ModelError::FullSidesError)?,
+        faces,
+        collision,
+        outline,
     })
 }
 
@@ -152,6 +384,29 @@ impl super::ConvertFrom<Model, ()> for md::Model {
     fn convert(from: Model, _: ()) -> Result<Self, Self::Error> { convert(from) }
 }
 
+impl LoadBytes for md::Model {
+    type Error = ParseError<ModelError>;
+    type Loader = ();
+
+    fn load_bytes(bytes: &[u8], _: &mut ()) -> Result<Self, ParseError<ModelError>> {
+        let model: Model = serde_yaml::from_slice(bytes)?;
+        convert(model).map_err(ParseError::ConvertError)
+    }
+}
+
+impl Load for md::Model {
+    type Error = ReadError<ParseError<ModelError>>;
+    type Loader = ();
+
+    fn load<S>(file: S, loader: &mut ()) -> Result<Self, ReadError<ParseError<ModelError>>>
+        where
+            S: AsRef<str>,
+    {
+        let bytes = std::fs::read(file.as_ref())?;
+        Self::load_bytes(&bytes, loader).map_err(ReadError::LoadError)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +422,10 @@ mod tests {
             norm: None,
             faces: None,
             full_sides: None,
+            smooth_normals: None,
+            transform: None,
+            collision: None,
+            outline: None,
         });
     }
 
@@ -232,11 +491,15 @@ mod tests {
                     st: None,
                     norm: None,
                     layer: None,
+                    st2: None,
+                    color: None,
+                    tint: None,
                     contact: None,
                     data: Some(Data {
                         pos: Some(vec![0, 1, 0]),
                         st: Some(vec![0, 1, 1]),
-                        norm: Some(0),
+                        norm: Some(OneOrMany::One(0)),
+                        st2: None,
                     }),
                 },
                 Face {
@@ -252,7 +515,10 @@ mod tests {
                         [1.0, 0.0],
                         [1.0, 1.0],
                     ]),
-                    norm: Some([0.0, 1.0, 0.0]),
+                    norm: Some(OneOrMany::One([0.0, 1.0, 0.0])),
+                    st2: None,
+                    color: None,
+                    tint: None,
                     layer: Some(6),
                     contact: Some("lr".to_owned()),
                     data: None,
@@ -268,13 +534,20 @@ mod tests {
                         [0.0, 0.0],
                         [1.0, 0.0],
                     ]),
-                    norm: Some([0.0, 0.0, 1.0]),
+                    norm: Some(OneOrMany::One([0.0, 0.0, 1.0])),
+                    st2: None,
+                    color: None,
+                    tint: None,
                     layer: Some(5),
                     contact: Some("fb".to_owned()),
                     data: None,
                 },
             ]),
             full_sides: Some("ud".to_owned()),
+            smooth_normals: None,
+            transform: None,
+            collision: None,
+            outline: None,
         });
     }
 
@@ -288,11 +561,17 @@ mod tests {
             norm: None,
             faces: None,
             full_sides: None,
+            smooth_normals: None,
+            transform: None,
+            collision: None,
+            outline: None,
         };
 
         let expected = md::Model {
             faces: vec![],
             full_sides: Sides::empty(),
+            collision: None,
+            outline: None,
         };
 
         assert_eq!(super::convert(model), Ok(expected));
@@ -316,15 +595,23 @@ mod tests {
                     st: None,
                     norm: None,
                     layer: None,
+                    st2: None,
+                    color: None,
+                    tint: None,
                     contact: None,
                     data: Some(Data {
                         pos: Some(vec![1, 1, 1]),
                         st: Some(vec![0, 0, 0]),
-                        norm: Some(0),
+                        norm: Some(OneOrMany::One(0)),
+                        st2: None,
                     }),
                 },
             ]),
             full_sides: None,
+            smooth_normals: None,
+            transform: None,
+            collision: None,
+            outline: None,
         };
 
         let err = ModelError::FaceError(FaceError::OutOfRange(ModelField::Pos, 1));
@@ -343,15 +630,23 @@ mod tests {
                     st: None,
                     norm: None,
                     layer: None,
+                    st2: None,
+                    color: None,
+                    tint: None,
                     contact: None,
                     data: Some(Data {
                         pos: Some(vec![1, 1, 1]),
                         st: Some(vec![0, 0, 0]),
-                        norm: Some(0),
+                        norm: Some(OneOrMany::One(0)),
+                        st2: None,
                     }),
                 },
             ]),
             full_sides: None,
+            smooth_normals: None,
+            transform: None,
+            collision: None,
+            outline: None,
         };
 
         let err = ModelError::FaceError(FaceError::IncorrectDataFormat);
@@ -383,11 +678,15 @@ mod tests {
                     st: None,
                     norm: None,
                     layer: None,
+                    st2: None,
+                    color: None,
+                    tint: None,
                     contact: None,
                     data: Some(Data {
                         pos: Some(vec![0, 1, 2]),
                         st: Some(vec![0, 1, 2]),
-                        norm: Some(0),
+                        norm: Some(OneOrMany::One(0)),
+                        st2: None,
                     }),
                 },
                 Face {
@@ -395,11 +694,15 @@ mod tests {
                     st: None,
                     norm: None,
                     layer: Some(2),
+                    st2: None,
+                    color: None,
+                    tint: None,
                     contact: None,
                     data: Some(Data {
                         pos: Some(vec![1, 0, 1, 0]),
                         st: Some(vec![1, 0, 1, 0]),
-                        norm: Some(1),
+                        norm: Some(OneOrMany::One(1)),
+                        st2: None,
                     }),
                 },
                 Face {
@@ -415,13 +718,20 @@ mod tests {
                         [1.0, 1.0],
                         [1.0, 0.0],
                     ]),
-                    norm: Some([0.0, 0.0, 1.0]),
+                    norm: Some(OneOrMany::One([0.0, 0.0, 1.0])),
+                    st2: None,
+                    color: None,
+                    tint: None,
                     layer: None,
                     contact: Some("ud".to_owned()),
                     data: None,
                 },
             ]),
             full_sides: Some(".".to_owned()),
+            smooth_normals: None,
+            transform: None,
+            collision: None,
+            outline: None,
         };
 
         let expected = md::Model {
@@ -446,6 +756,9 @@ mod tests {
                     ]),
                     contact: Sides::empty(),
                     layer: 0,
+                    colors: vec![],
+                    st2: vec![],
+                    tint: None,
                 },
                 fc::Face {
                     vertexes: fc::FaceVertexes::Square([
@@ -472,6 +785,9 @@ mod tests {
                     ]),
                     contact: Sides::empty(),
                     layer: 2,
+                    colors: vec![],
+                    st2: vec![],
+                    tint: None,
                 },
                 fc::Face {
                     vertexes: fc::FaceVertexes::Square([
@@ -498,11 +814,614 @@ mod tests {
                     ]),
                     contact: Up | Down,
                     layer: 0,
+                    colors: vec![],
+                    st2: vec![],
+                    tint: None,
                 },
             ],
             full_sides: Sides::all(),
+            collision: None,
+            outline: None,
         };
 
         assert_eq!(super::convert(model), Ok(expected));
     }
+
+    #[test]
+    fn convert_auto() {
+        let code = r#"
+        pos:
+          - [ 0.0, 1.0, 0.0 ]
+          - [ 0.0, 1.0, 1.0 ]
+          - [ 1.0, 1.0, 1.0 ]
+          - [ 1.0, 1.0, 0.0 ]
+          - [ 0.0, 0.0, 0.0 ]
+          - [ 1.0, 0.0, 0.0 ]
+          - [ 1.0, 0.0, 1.0 ]
+        st:
+          - [ 0.0, 0.0 ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2, 3 ]
+                st: [ 0, 0, 0, 0 ]
+            contact: auto
+          - data:
+                pos: [ 4, 5, 6 ]
+                st: [ 0, 0, 0 ]
+            contact: auto
+          - data:
+                pos: [ 4, 6, 2 ]
+                st: [ 0, 0, 0 ]
+            contact: auto
+        full_sides: auto
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+
+        assert_eq!(model.faces[0].contact, Up.into());
+        assert_eq!(model.faces[1].contact, Down.into());
+        assert_eq!(model.faces[2].contact, Sides::empty());
+        assert_eq!(model.full_sides, Up.into());
+    }
+
+    #[test]
+    fn convert_polygon() {
+        let code = r#"
+        faces:
+          - pos:
+              - [ 0.0, 0.0, 0.0 ]
+              - [ 1.0, 0.0, 0.0 ]
+              - [ 1.0, 0.5, 0.0 ]
+              - [ 0.5, 1.0, 0.0 ]
+              - [ 0.0, 0.5, 0.0 ]
+            st:
+              - [ 0.0, 0.0 ]
+              - [ 1.0, 0.0 ]
+              - [ 1.0, 0.5 ]
+              - [ 0.5, 1.0 ]
+              - [ 0.0, 0.5 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+
+        match &model.faces[0].vertexes {
+            fc::FaceVertexes::Polygon(vs) => assert_eq!(vs.len(), 5),
+            vs => panic!("expected polygon, got {:?}", vs),
+        }
+
+        let (vertexes, indexes) = model.get_indexed_vertexes();
+        assert_eq!(vertexes.len(), 5);
+        assert_eq!(indexes.len(), 9);
+
+        let code = r#"
+        faces:
+          - pos:
+              - [ 0.0, 0.0, 0.0 ]
+              - [ 1.0, 0.0, 0.0 ]
+              - [ 1.0, 1.0, 0.0 ]
+              - [ 0.5, -0.5, 0.0 ]
+              - [ 0.0, 1.0, 0.0 ]
+            st:
+              - [ 0.0, 0.0 ]
+              - [ 1.0, 0.0 ]
+              - [ 1.0, 1.0 ]
+              - [ 0.5, 0.0 ]
+              - [ 0.0, 1.0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let err = ModelError::FaceError(FaceError::TriangulationError);
+        assert_eq!(super::convert(model), Err(err));
+    }
+
+    #[test]
+    fn convert_wrong_vertex_number() {
+        let code = r#"
+        faces:
+          - pos:
+              - [ 0.0, 0.0, 0.0 ]
+              - [ 1.0, 0.0, 0.0 ]
+            st:
+              - [ 0.0, 0.0 ]
+              - [ 1.0, 0.0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let err = ModelError::FaceError(FaceError::WrongVertexNumber(ModelField::Pos));
+        assert_eq!(super::convert(model), Err(err));
+
+        let code = r#"
+        pos:
+          - [ 0.0, 0.0, 0.0 ]
+          - [ 1.0, 0.0, 0.0 ]
+          - [ 1.0, 1.0, 0.0 ]
+        st:
+          - [ 0.0, 0.0 ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2 ]
+                st: [ 0, 0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let err = ModelError::FaceError(FaceError::WrongVertexNumber(ModelField::DataSt));
+        assert_eq!(super::convert(model), Err(err));
+    }
+
+    #[test]
+    fn convert_vertex_normals() {
+        use glm::vec3;
+
+        let code = r#"
+        pos:
+          - [ 0.0, 0.0, 0.0 ]
+          - [ 1.0, 0.0, 0.0 ]
+          - [ 1.0, 1.0, 0.0 ]
+        st:
+          - [ 0.0, 0.0 ]
+        norm:
+          - [ 0.0, 0.0, 1.0 ]
+          - [ 1.0, 0.0, 0.0 ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2 ]
+                st: [ 0, 0, 0 ]
+                norm: [ 0, 1, 0 ]
+          - pos:
+              - [ 0.0, 0.0, 0.0 ]
+              - [ 1.0, 0.0, 0.0 ]
+              - [ 1.0, 1.0, 0.0 ]
+            st:
+              - [ 0.0, 0.0 ]
+              - [ 1.0, 0.0 ]
+              - [ 1.0, 1.0 ]
+            norm:
+              - [ 0.0, 1.0, 0.0 ]
+              - [ 0.0, 0.0, 1.0 ]
+              - [ 0.0, 1.0, 0.0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+
+        let norms: Vec<_> = model.faces[0].vertexes.as_slice().iter().map(|v| v.norm).collect();
+        assert_eq!(norms, [vec3(0., 0., 1.), vec3(1., 0., 0.), vec3(0., 0., 1.)]);
+
+        let norms: Vec<_> = model.faces[1].vertexes.as_slice().iter().map(|v| v.norm).collect();
+        assert_eq!(norms, [vec3(0., 1., 0.), vec3(0., 0., 1.), vec3(0., 1., 0.)]);
+
+        let code = r#"
+        norm:
+          - [ 0.0, 0.0, 1.0 ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2 ]
+                st: [ 0, 0, 0 ]
+                norm: [ 0, 0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let err = ModelError::FaceError(FaceError::WrongVertexNumber(ModelField::DataNorm));
+        assert_eq!(super::convert(model), Err(err));
+
+        for norm in &["3", "[ 0, 3, 0 ]"] {
+            let code = format!(r#"
+            pos: [ [ 0.0, 0.0, 0.0 ], [ 1.0, 0.0, 0.0 ], [ 1.0, 1.0, 0.0 ] ]
+            st: [ [ 0.0, 0.0 ] ]
+            norm: [ [ 0.0, 0.0, 1.0 ] ]
+            faces:
+              - data:
+                    pos: [ 0, 1, 2 ]
+                    st: [ 0, 0, 0 ]
+                    norm: {}
+            "#, norm);
+
+            let model: Model = serde_yaml::from_str(&code).unwrap();
+            let err = ModelError::FaceError(FaceError::OutOfRange(ModelField::Norm, 3));
+            assert_eq!(super::convert(model), Err(err));
+        }
+    }
+
+    #[test]
+    fn convert_smooth_normals() {
+        use glm::vec3;
+
+        let code = r#"
+        pos:
+          - [ 0.0, 1.0, 0.0 ]
+          - [ 0.0, 1.0, 1.0 ]
+          - [ 1.0, 1.0, 1.0 ]
+          - [ 1.0, 1.0, 0.0 ]
+          - [ 1.0, 0.0, 0.0 ]
+          - [ 1.0, 0.0, 1.0 ]
+        st:
+          - [ 0.0, 0.0 ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2, 3 ]
+                st: [ 0, 0, 0, 0 ]
+          - data:
+                pos: [ 4, 3, 2, 5 ]
+                st: [ 0, 0, 0, 0 ]
+        smooth_normals: true
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+
+        let edge = vec3(1., 1., 0.).normalize();
+        let top = model.faces[0].vertexes.as_slice();
+        let side = model.faces[1].vertexes.as_slice();
+
+        assert_eq!(top[0].norm, vec3(0., 1., 0.));
+        assert_eq!(top[2].norm, edge);
+        assert_eq!(side[0].norm, vec3(1., 0., 0.));
+        assert_eq!(side[1].norm, edge);
+    }
+
+    #[test]
+    fn convert_transform() {
+        use glm::vec3;
+        use crate::engine::{
+            axis::Axis,
+            shell_transform::{Shell, ShellTransform},
+            vertex::Vertex,
+        };
+
+        let near = |a: glm::Vec3, b: glm::Vec3| (a - b).norm() < 1e-5;
+
+        let code = r#"
+        pos:
+          - [ 0.0, 0.0, 1.0 ]
+          - [ 1.0, 0.0, 1.0 ]
+          - [ 1.0, 1.0, 1.0 ]
+          - [ 0.0, 1.0, 1.0 ]
+        st:
+          - [ 0.0, 0.0 ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2, 3 ]
+                st: [ 0, 0, 0, 0 ]
+            contact: auto
+        full_sides: auto
+        transform:
+          - scale: [ 1.0, 1.0, 0.5 ]
+          - rotate: { angle: 90, axis: [ 0, 1, 0 ] }
+          - translate: [ 0.0, 0.0, 0.0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+        let face = &model.faces[0];
+        let vs = face.vertexes.as_slice();
+
+        assert!(near(vs[0].pos, vec3(0.75, 0., 1.)));
+        assert!(near(vs[2].pos, vec3(0.75, 1., 0.)));
+        assert!(vs.iter().all(|v| near(v.norm, vec3(1., 0., 0.))));
+        assert_eq!(face.contact, Sides::empty());
+        assert_eq!(model.full_sides, Sides::empty());
+
+        let code = r#"
+        pos: [ [ 0, 0, 0 ], [ 1, 0, 0 ], [ 0, 1, 0 ] ]
+        st: [ [ 0, 0 ] ]
+        faces:
+          - data: { pos: [ 0, 1, 2 ], st: [ 0, 0, 0 ] }
+        transform:
+          - scale: [ -1, 1, 1 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+        let vs = model.faces[0].vertexes.as_slice();
+
+        assert!(near(vs[0].pos, vec3(1., 1., 0.)));
+        assert!(near(vs[2].pos, vec3(1., 0., 0.)));
+        let norm = vs[0].norm;
+        assert!(near(norm, vec3(0., 0., norm.z.signum())));
+        assert!(near(calc_normal(vs[0].pos, vs[1].pos, vs[2].pos), norm));
+
+        let mut shell = Shell::new();
+        shell.turn_clockwise(Axis::X);
+
+        let (vertexes, indexes) = model.get_shell_vertexes_as::<Vertex>(shell);
+        assert_eq!(indexes, [0, 1, 2]);
+        assert!(near(vertexes[0].pos, vec3(1., 0., 0.)));
+        assert!(near(vertexes[0].norm, vec3(0., norm.z, 0.)));
+
+        let code = "{ faces: [], transform: [ { rotate: { angle: 10, axis: [ 0, 0, 0 ] } } ] }";
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        assert_eq!(super::convert(model), Err(ModelError::TransformError));
+
+        for scale in &["[ 1, 2 ]", "0", "[ 1, 0, 1 ]", "[ 1, .nan, 1 ]"] {
+            let code = format!("{{ faces: [], transform: [ {{ scale: {} }} ] }}", scale);
+            let model: Model = serde_yaml::from_str(&code).unwrap();
+            assert_eq!(super::convert(model), Err(ModelError::TransformError));
+        }
+
+        let code = "{ faces: [], transform: [ { scale: [ -1, 0.5, 1 ] } ] }";
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        assert!(super::convert(model).is_ok());
+    }
+
+    #[test]
+    fn transformed_vertexes() {
+        use crate::engine::{
+            axis::Axis,
+            shell_transform::{Shell, ShellTransform},
+            transform::Transform,
+            vertex::ExtendedVertex,
+        };
+
+        let code = r#"
+        pos: [ [ 0.0, 0.0, 0.0 ], [ 1.0, 0.0, 0.0 ], [ 1.0, 0.5, 0.0 ], [ 0.0, 1.0, 0.0 ] ]
+        st: [ [ 0.0, 0.0 ], [ 1.0, 0.0 ], [ 1.0, 0.5 ], [ 0.0, 1.0 ] ]
+        faces:
+          - data: { pos: [ 0, 1, 2, 3 ], st: [ 0, 1, 2, 3 ] }
+            color: [ [ 1.0, 0.0, 0.0, 1.0 ], [ 0.0, 1.0, 0.0, 1.0 ], [ 0.0, 0.0, 1.0, 1.0 ], [ 1.0, 1.0, 1.0, 1.0 ] ]
+          - pos: [ [ 0.0, 0.0, 1.0 ], [ 1.0, 0.0, 1.0 ], [ 1.0, 1.0, 1.0 ], [ 0.75, 0.25, 1.0 ], [ 0.5, 1.0, 1.0 ], [ 0.0, 1.0, 1.0 ] ]
+            st: [ [ 0.0, 0.0 ], [ 1.0, 0.0 ], [ 1.0, 1.0 ], [ 0.75, 0.25 ], [ 0.5, 1.0 ], [ 0.0, 1.0 ] ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+        assert_eq!(model.faces[0].colors.len(), 4);
+
+        let mut mirror = Shell::new();
+        mirror.flip(Axis::X).turn_clockwise(Axis::Y);
+
+        let transforms = [
+            Transform::from(mirror),
+            Transform::scale(glm::vec3(2., -1., 0.5), glm::vec3(0., 0., 0.)),
+            Transform::scale(glm::vec3(-1., 1., 1.), glm::vec3(0.5, 0.5, 0.5)),
+        ];
+
+        for transform in &transforms {
+            let mut copy = model.clone();
+            copy.transform(transform);
+
+            assert_eq!(model.get_transformed_vertexes_as::<ExtendedVertex>(transform), copy.get_indexed_vertexes_as());
+            assert_eq!(model.get_transformed_vertexes_as::<Vertex>(transform), copy.get_indexed_vertexes_as());
+        }
+    }
+
+    #[test]
+    fn convert_sides() {
+        let face = |contact: &str| format!(
+            "{{ pos: [[0, 0, 0], [1, 0, 0], [0, 1, 0]], st: [[0, 0], [1, 0], [0, 1]], \
+               faces: [{{ data: {{ pos: [0, 1, 2], st: [0, 1, 2] }}, contact: '{}' }}], full_sides: '{}' }}",
+            contact.split('|').next().unwrap(),
+            contact.split('|').nth(1).unwrap(),
+        );
+
+        let convert = |code: String| super::convert(serde_yaml::from_str::<Model>(&code).unwrap());
+
+        let model = convert(face("fb|.")).unwrap();
+        assert_eq!(model.faces[0].contact, Front | Back);
+        assert_eq!(model.full_sides, Sides::all());
+
+        assert_eq!(convert(face("fx|ud")), Err(ModelError::FaceError(FaceError::ContactError(SidesError::UnknownSide("x".to_owned())))));
+        assert_eq!(convert(face("f|udu")), Err(ModelError::FullSidesError(SidesError::DuplicateSide('u'))));
+
+        let err = convert(face("f|up")).unwrap_err();
+        assert_eq!(err.to_string(), "Model Error: Unknown Side (in full_sides at 'p') [use f, b, u, d, l, r or '.' for all sides]");
+    }
+
+    #[test]
+    fn convert_collision() {
+        use glm::vec3;
+        use crate::engine::{axis::Axis, shell_transform::{Shell, ShellTransform}};
+
+        let code = br#"
+        faces:
+          - pos: [ [ 0.0, 0.0, 0.0 ], [ 1.0, 0.0, 0.0 ], [ 1.0, 0.5, 1.0 ] ]
+            st: [ [ 0.0, 0.0 ], [ 1.0, 0.0 ], [ 1.0, 1.0 ] ]
+        "#;
+
+        let model = md::Model::load_bytes(code, &mut ()).unwrap();
+        assert!(model.collision.is_none());
+        assert_eq!(model.collision_boxes(Shell::new()), [Aabb::new(vec3(0., 0., 0.), vec3(1., 0.5, 1.))]);
+
+        let mut shell = Shell::new();
+        shell.flip(Axis::Y);
+        assert_eq!(model.collision_boxes(shell), [Aabb::new(vec3(0., 0.5, 0.), vec3(1., 1., 1.))]);
+
+        let code = br#"
+        collision:
+          - { from: [ 0.0, 0.0, 0.0 ], to: [ 1.0, 0.5, 1.0 ] }
+          - { from: [ 0.5, 0.5, 0.0 ], to: [ 1.0, 1.0, 1.0 ] }
+        transform:
+          - translate: [ 0.0, 0.25, 0.0 ]
+        "#;
+
+        let model = md::Model::load_bytes(code, &mut ()).unwrap();
+        assert_eq!(model.collision_boxes(Shell::new()), [
+            Aabb::new(vec3(0., 0.25, 0.), vec3(1., 0.75, 1.)),
+            Aabb::new(vec3(0.5, 0.75, 0.), vec3(1., 1.25, 1.)),
+        ]);
+
+        let model = md::Model::load_bytes(b"collision: []", &mut ()).unwrap();
+        assert!(model.collision_boxes(Shell::new()).is_empty());
+        assert!(md::Model::load_bytes(b"faces: []", &mut ()).unwrap().collision_boxes(Shell::new()).is_empty());
+    }
+
+    #[test]
+    fn convert_outline() {
+        use glm::vec3;
+        use crate::engine::{axis::Axis, shell_transform::{Shell, ShellTransform}};
+
+        let code = br#"
+        faces:
+          - pos: [ [ 0.5, 0.0, 0.0 ], [ 0.5, 0.0, 1.0 ], [ 0.5, 1.0, 1.0 ], [ 0.5, 1.0, 0.0 ] ]
+            st: [ [ 0.0, 0.0 ], [ 1.0, 0.0 ], [ 1.0, 1.0 ], [ 0.0, 1.0 ] ]
+        outline:
+          - { from: [ 0.25, 0.0, 0.0 ], to: [ 0.75, 1.0, 1.0 ] }
+        "#;
+
+        let model = md::Model::load_bytes(code, &mut ()).unwrap();
+        assert_eq!(model.bounds(), Some(Aabb::new(vec3(0.5, 0., 0.), vec3(0.5, 1., 1.))));
+        assert_eq!(model.outline_boxes(Shell::new()), [Aabb::new(vec3(0.25, 0., 0.), vec3(0.75, 1., 1.))]);
+        assert!(model.collision_boxes(Shell::new())[0].size().x == 0.);
+
+        let mut shell = Shell::new();
+        shell.turn_clockwise(Axis::Y);
+        assert_eq!(model.get_shell_bounds(shell), Some(Aabb::new(vec3(0., 0., 0.5), vec3(1., 1., 0.5))));
+
+        let segments = model.get_outline_segments(shell);
+        assert_eq!(segments.len(), 12);
+        assert!(segments.iter().all(|(a, b)| a.z >= 0.25 && b.z <= 0.75));
+
+        let model = md::Model::load_bytes(b"faces: []", &mut ()).unwrap();
+        assert!(model.bounds().is_none());
+        assert!(model.get_outline_segments(Shell::new()).is_empty());
+    }
+
+    #[test]
+    fn convert_extended() {
+        use glm::{vec2, vec4};
+        use crate::engine::vertex::ExtendedVertex;
+
+        let code = r#"
+        pos:
+          - [ 0.0, 0.0, 0.0 ]
+          - [ 1.0, 0.0, 0.0 ]
+          - [ 1.0, 1.0, 0.0 ]
+        st:
+          - [ 0.0, 0.0 ]
+          - [ 1.0, 0.0 ]
+          - [ 1.0, 1.0 ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2 ]
+                st: [ 0, 1, 2 ]
+                st2: [ 2, 2, 2 ]
+            color: [ 1.0, 0.0, 0.0, 1.0 ]
+          - pos:
+              - [ 0.0, 0.0, 0.0 ]
+              - [ 1.0, 0.0, 0.0 ]
+              - [ 1.0, 1.0, 0.0 ]
+            st:
+              - [ 0.0, 0.0 ]
+              - [ 1.0, 0.0 ]
+              - [ 1.0, 1.0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+        let (vertexes, indexes) = model.get_indexed_vertexes_as::<ExtendedVertex>();
+
+        assert_eq!(vertexes.len(), 6);
+        assert_eq!(indexes, [0, 1, 2, 3, 4, 5]);
+
+        assert_eq!(vertexes[0].color, vec4(1., 0., 0., 1.));
+        assert_eq!(vertexes[0].st2, vec2(1., 1.));
+        assert_eq!(vertexes[0].tangent, vec4(1., 0., 0., 1.));
+
+        assert_eq!(vertexes[4].color, vec4(1., 1., 1., 1.));
+        assert_eq!(vertexes[4].st2, vec2(1., 0.));
+
+        let code = r#"
+        pos: [ [ 0.0, 0.0, 0.0 ], [ 1.0, 0.0, 0.0 ], [ 1.0, 1.0, 0.0 ] ]
+        st: [ [ 0.0, 0.0 ] ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2 ]
+                st: [ 0, 0, 0 ]
+                st2: [ 0, 4, 0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let err = ModelError::FaceError(FaceError::OutOfRange(ModelField::St2, 4));
+        assert_eq!(super::convert(model), Err(err));
+
+        let code = r#"
+        faces:
+          - pos:
+              - [ 0.0, 0.0, 0.0 ]
+              - [ 1.0, 0.0, 0.0 ]
+              - [ 1.0, 1.0, 0.0 ]
+            st:
+              - [ 0.0, 0.0 ]
+              - [ 1.0, 0.0 ]
+              - [ 1.0, 1.0 ]
+            color:
+              - [ 1.0, 0.0, 0.0, 1.0 ]
+              - [ 1.0, 0.0, 0.0, 1.0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let err = ModelError::FaceError(FaceError::WrongVertexNumber(ModelField::Color));
+        assert_eq!(super::convert(model), Err(err));
+    }
+
+    #[test]
+    fn convert_tint() {
+        use glm::vec4;
+        use crate::engine::{
+            axis::Axis,
+            point::Point,
+            shell_transform::{Shell, ShellTransform},
+            vertex::ExtendedVertex,
+        };
+
+        let code = r#"
+        pos:
+          - [ 0.0, 1.0, 0.0 ]
+          - [ 0.0, 1.0, 1.0 ]
+          - [ 1.0, 1.0, 1.0 ]
+        st:
+          - [ 0.0, 0.0 ]
+        faces:
+          - data:
+                pos: [ 0, 1, 2 ]
+                st: [ 0, 0, 0 ]
+            tint: 1
+            color: [ 0.5, 1.0, 1.0, 1.0 ]
+          - data:
+                pos: [ 0, 1, 2 ]
+                st: [ 0, 0, 0 ]
+        "#;
+
+        let model: Model = serde_yaml::from_str(code).unwrap();
+        let model = super::convert(model).unwrap();
+        assert_eq!(model.faces[0].tint, Some(1));
+        assert_eq!(model.faces[1].tint, None);
+
+        let mut calls = vec![];
+        let (vertexes, _) = model.get_tinted_vertexes(Point(1, 2, 3), |slot, point| {
+            calls.push((slot, point));
+            vec4(0.0, 0.5, 1.0, 1.0)
+        });
+
+        assert_eq!(calls, [(1, Point(1, 2, 3))]);
+        assert!(vertexes[0..3].iter().all(|v| v.color == vec4(0.0, 0.5, 1.0, 1.0)));
+        assert!(vertexes[3..6].iter().all(|v| v.color == vec4(1.0, 1.0, 1.0, 1.0)));
+
+        let mut shell = Shell::new();
+        shell.turn_clockwise(Axis::Y).flip(Axis::X);
+
+        let (vertexes, indexes) = model.get_shell_tinted_vertexes(shell, Point(1, 2, 3), |_, _| vec4(0.0, 0.5, 1.0, 1.0));
+        let (plain, plain_indexes): (Vec<ExtendedVertex>, _) = model.get_shell_vertexes_as(shell);
+        assert_eq!(indexes, plain_indexes);
+
+        for (v, p) in vertexes.iter().zip(&plain) {
+            assert_eq!((v.pos, v.norm, v.st), (p.pos, p.norm, p.st));
+        }
+
+        assert!(vertexes[0..3].iter().all(|v| v.color == vec4(0.0, 0.5, 1.0, 1.0)));
+        assert!(vertexes[3..6].iter().all(|v| v.color == vec4(1.0, 1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn load_bytes() {
+        use crate::vfs::PakVfs;
+
+        let code = b"faces: []\nfull_sides: ud\n";
+        let model = md::Model::load_bytes(code, &mut ()).unwrap();
+        assert_eq!(model.full_sides, Up | Down);
+
+        let err = md::Model::load_from(&PakVfs::default(), "model.yml", &mut ()).unwrap_err();
+        assert!(matches!(err, crate::load::ReadError::IoError(_)));
+    }
 }
@@ -12,6 +12,7 @@ pub(crate) struct State {
     model: Option<u32>,
     layers: Option<Vec<u32>>,
     transform: Option<Vec<String>>,
+    rotations: Option<String>,
 }
 
 use crate::{
@@ -19,18 +20,24 @@ use crate::{
         tile as tl,
         state as st,
         shell_transform::*,
+        orientation::{Orientation, Rotations},
+        sides::*,
+        handle::Handle,
+        model::Symmetry,
         resource::Resource,
     },
+    asset::Asset,
     error::tile::*,
-    load::Load,
+    load::{Load, LoadBytes, ReadError},
+    parse::ParseError,
 };
 
 type TileLoaders<M, T> = (Resource<M>, Resource<T>);
-type TileResult<M, T> = Result<tl::Tile, TileError<M, T>>;
+type TileResult<M, T> = Result<tl::Tile<M, T>, TileError<<M as Load>::Error, <T as Load>::Error>>;
 
-fn convert<M, T>(src: Tile, loaders: &mut TileLoaders<M, T>) -> TileResult<M::Error, T::Error>
+fn convert<M, T>(src: Tile, loaders: &mut TileLoaders<M, T>) -> TileResult<M, T>
     where
-        M: Load<Loader=()>,
+        M: Load<Loader=()> + Symmetry,
         T: Load<Loader=()>,
 {
     let (model_loader, texture_loader) = loaders;
@@ -40,7 +47,7 @@ fn convert<M, T>(src: Tile, loaders: &mut TileLoaders<M, T>) -> TileResult<M::Er
     let states = src.states.unwrap_or_default();
 
     let convert_state = |state: State| {
-        Ok(st::State {
+        let base = st::State {
             model: {
                 let model_idx = state.model.ok_or(StateError::NoModelDefined)? as usize;
 
@@ -76,7 +83,7 @@ fn convert<M, T>(src: Tile, loaders: &mut TileLoaders<M, T>) -> TileResult<M::Er
                     Err(StateError::OutOfRange(TileField::Textures, l as usize))?
                 }
 
-                let layers_result: Result<Vec<u32>, _> = layers
+                let layers_result: Result<Vec<Handle<T>>, _> = layers
                     .into_iter()
                     .map(|l| {
                         let l = l as usize;
@@ -87,38 +94,135 @@ fn convert<M, T>(src: Tile, loaders: &mut TileLoaders<M, T>) -> TileResult<M::Er
 
                         texture_loader.load(&*texture_file)
                             .map_err(|e| StateError::TextureError(e))
-                            .map(|(id, _)| id as u32)
+                            .map(|(handle, _)| handle)
                     })
                     .collect();
 
                 layers_result?
             },
-        })
+
+            facing: Front,
+        };
+
+        let rotations = match state.rotations {
+            Some(r) => {
+                use std::convert::TryFrom;
+                Some(Rotations::try_from(&*r).map_err(|_| StateError::RotationsError)?)
+            }
+            None => None,
+        };
+
+        let model = model_loader.get(base.model);
+        Ok(expand(base, rotations, model))
     };
 
-    let states_result: Result<Vec<st::State>, StateError<_, _>> = states
+    let states_result: Result<Vec<Vec<st::State<M, T>>>, StateError<_, _>> = states
         .into_iter()
         .map(convert_state)
         .collect();
 
-    Ok(tl::Tile {
-        states: states_result?,
-        id: 0,
-    })
+    let states: Vec<st::State<M, T>> = states_result?.into_iter().flatten().collect();
+
+    // Pinned only once the whole tile converted, so a failed tile leaves its entries evictable
+    let tile = tl::Tile { states, id: 0 };
+    tile.retain(model_loader, texture_loader);
+
+    Ok(tile)
 }
 
-impl<M, T> super::ConvertFrom<Tile, &mut TileLoaders<M, T>> for tl::Tile
+// Variants facing the same side whose model looks the same are dropped,
+// states declared separately in the tile are always kept
+fn expand<M, T>(base: st::State<M, T>, rotations: Option<Rotations>, model: Option<&M>) -> Vec<st::State<M, T>>
     where
-        M: Load<Loader=()>,
+        M: Symmetry,
+{
+    let orientation = Orientation::from(base.shell);
+
+    let (group, reference) = match rotations {
+        Some(r) => (r.orientations(), r.reference()),
+        None => (vec![Orientation::identity()], Front),
+    };
+
+    let mut states: Vec<st::State<M, T>> = Vec::new();
+
+    for o in group {
+        let full = orientation.then(o);
+
+        let state = st::State {
+            model: base.model,
+            shell: full.into(),
+            layers: base.layers.clone(),
+            facing: full.side(reference),
+        };
+
+        let duplicate = states.iter().any(|s| {
+            s.facing == state.facing && match model {
+                Some(model) => model.same_shape(s.shell, state.shell),
+                None => s.shell == state.shell,
+            }
+        });
+
+        if !duplicate {
+            states.push(state);
+        }
+    }
+
+    states
+}
+
+impl<M, T> super::ConvertFrom<Tile, &mut TileLoaders<M, T>> for tl::Tile<M, T>
+    where
+        M: Load<Loader=()> + Symmetry,
         T: Load<Loader=()>,
 {
     type Error = TileError<M::Error, T::Error>;
 
-    fn convert(from: Tile, loader: &mut TileLoaders<M, T>) -> TileResult<M::Error, T::Error> {
+    fn convert(from: Tile, loader: &mut TileLoaders<M, T>) -> TileResult<M, T> {
         convert(from, loader)
     }
 }
 
+// Only the tile file itself comes from the bytes, its models and textures
+// are still loaded by the resources in `loader`
+impl<M, T> LoadBytes for tl::Tile<M, T>
+    where
+        M: Load<Loader=()> + Symmetry,
+        T: Load<Loader=()>,
+{
+    type Error = ParseError<TileError<M::Error, T::Error>>;
+    type Loader = TileLoaders<M, T>;
+
+    fn load_bytes(bytes: &[u8], loader: &mut Self::Loader) -> Result<Self, Self::Error> {
+        let tile: Tile = serde_yaml::from_slice(bytes)?;
+        convert(tile, loader).map_err(ParseError::ConvertError)
+    }
+}
+
+impl<M, T> Load for tl::Tile<M, T>
+    where
+        M: Load<Loader=()> + Symmetry,
+        T: Load<Loader=()>,
+{
+    type Error = ReadError<ParseError<TileError<M::Error, T::Error>>>;
+    type Loader = TileLoaders<M, T>;
+
+    fn load<S>(file: S, loader: &mut Self::Loader) -> Result<Self, Self::Error>
+        where
+            S: AsRef<str>,
+    {
+        let bytes = std::fs::read(file.as_ref())?;
+        Self::load_bytes(&bytes, loader).map_err(ReadError::LoadError)
+    }
+}
+
+impl<M, T> Asset for tl::Tile<M, T>
+    where
+        M: Load<Loader=()> + Symmetry,
+        T: Load<Loader=()>,
+{
+    const DIR: &'static str = "tiles";
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,18 +284,23 @@ mod tests {
                         "turn_y".to_owned(),
                         "turn_-z".to_owned(),
                     ]),
+                    rotations: None,
                 },
                 State {
                     model: Some(2),
                     layers: None,
                     transform: None,
+                    rotations: None,
                 },
             ]),
         });
     }
 
+    // Deliberately without derives, tiles and states compare and print through their handles
     struct Model;
 
+    impl Symmetry for Model {}
+
     impl Load for Model {
         type Error = ();
         type Loader = ();
@@ -224,45 +333,263 @@ mod tests {
                     model: Some(1),
                     layers: Some(vec![1, 1]),
                     transform: Some(vec!["turn_x".to_owned()]),
+                    rotations: None,
                 },
                 State {
                     model: Some(0),
                     layers: Some(vec![0, 1]),
                     transform: Some(vec!["flip_x".to_owned()]),
+                    rotations: None,
                 },
                 State {
                     model: Some(2),
                     layers: Some(vec![2, 2]),
                     transform: Some(vec!["turn_-z".to_owned()]),
+                    rotations: None,
                 },
             ]),
         };
 
+        let model_loader: Resource<Model> = Resource::new();
+        let texture_loader: Resource<Texture> = Resource::new();
+        let mut loader = (model_loader, texture_loader);
+
+        let converted = super::convert(tile, &mut loader).unwrap();
+        let (models, textures) = &loader;
+
+        let models_loaded: Vec<_> = models.entries().map(|(handle, _)| handle).collect();
+        let textures_loaded: Vec<_> = textures.entries().map(|(handle, _)| handle).collect();
+        let model = |idx: usize| models_loaded[idx];
+        let texture = |idx: usize| textures_loaded[idx];
+
         let expected = tl::Tile {
             states: vec![
                 st::State {
-                    model: 0,
+                    model: model(0),
                     shell: *Shell::new().turn_counter_clockwise(Axis::X),
-                    layers: vec![0, 0],
+                    layers: vec![texture(0), texture(0)],
+                    facing: Down,
                 },
                 st::State {
-                    model: 1,
+                    model: model(1),
                     shell: *Shell::new().flip(Axis::X),
-                    layers: vec![1, 0],
+                    layers: vec![texture(1), texture(0)],
+                    facing: Front,
                 },
                 st::State {
-                    model: 2,
+                    model: model(2),
                     shell: *Shell::new().turn_clockwise(Axis::Z),
-                    layers: vec![1, 1],
+                    layers: vec![texture(1), texture(1)],
+                    facing: Front,
                 },
             ],
             id: 0,
         };
 
-        let model_loader: Resource<Model> = Resource::new();
-        let texture_loader: Resource<Texture> = Resource::new();
-        let mut loader = (model_loader, texture_loader);
+        assert_eq!(converted, expected);
+        assert_eq!(converted.clone(), expected);
+        assert!(format!("{:?}", converted.states[0]).starts_with("State { model: Handle(0v0)"));
+
+        assert_eq!(converted.models(), [model(0), model(1), model(2)]);
+        assert_eq!(converted.textures(), [texture(0), texture(1)]);
+        assert!(converted.models().into_iter().all(|m| models.refs(m) == Some(1)));
+        assert!(converted.textures().into_iter().all(|t| textures.refs(t) == Some(1)));
+    }
+
+    #[test]
+    fn pin_resources() {
+        let tile = |model, layer| Tile {
+            models: Some(vec!["m1".to_owned(), "m2".to_owned()]),
+            textures: Some(vec!["t1".to_owned()]),
+            states: Some(vec![State { model: Some(model), layers: Some(vec![layer]), transform: None, rotations: None }]),
+        };
+
+        let mut loader = (Resource::<Model>::new(), Resource::<Texture>::new());
+        let first = super::convert(tile(0, 0), &mut loader).unwrap();
+        let second = super::convert(tile(0, 0), &mut loader).unwrap();
+        assert!(super::convert(tile(1, 1), &mut loader).is_err());
+
+        let (models, textures) = &mut loader;
+        assert_eq!(models.refs(first.states[0].model), Some(2));
+
+        // Only the model of the failed tile is unused
+        assert_eq!(models.evict_unused().len(), 1);
+        assert!(textures.evict_unused().is_empty());
+        assert!(models.is_valid(second.states[0].model));
+
+        first.release(models, textures);
+        second.release(models, textures);
+        assert_eq!(models.evict_unused().len(), 1);
+        assert_eq!(textures.evict_unused().len(), 1);
+        assert_eq!((models.len(), textures.len()), (0, 0));
+    }
+
+    #[test]
+    fn load_from() {
+        use crate::vfs::PakVfs;
+
+        let mut pak = PakVfs::default();
+        pak.insert("tiles/stone.yml", b"{ models: [m1], textures: [t1], states: [ { model: 0, layers: [0] } ] }".to_vec()).unwrap();
+        pak.insert("tiles/broken.yml", b"{ models: [m1], states: [ { model: 0, layers: [0] } ] }".to_vec()).unwrap();
+
+        let mut tiles = Resource::<tl::Tile<Model, Texture>>::new();
+        let mut loader = (Resource::<Model>::new(), Resource::<Texture>::new());
+
+        let (_, stone) = tiles.load_from(&pak, "tiles/stone.yml", &mut loader).unwrap();
+        assert_eq!(stone.states.len(), 1);
+        assert_eq!((loader.0.len(), loader.1.len()), (1, 1));
+
+        let err = tiles.load_from(&pak, "tiles/broken.yml", &mut loader).unwrap_err();
+        assert!(matches!(
+            err,
+            ReadError::LoadError(ParseError::ConvertError(TileError::StateError(StateError::OutOfRange(TileField::Textures, 0))))
+        ));
+        assert!(matches!(tiles.load_from(&pak, "tiles/dirt.yml", &mut loader), Err(ReadError::IoError(_))));
+    }
+
+    #[test]
+    fn convert_rotations() {
+        let state = |model, transform: &[&str], rotations: &str| State {
+            model: Some(model),
+            layers: Some(vec![0]),
+            transform: Some(transform.iter().map(|&t| t.to_owned()).collect()),
+            rotations: Some(rotations.to_owned()),
+        };
+
+        let tile = |states| Tile {
+            models: Some(vec!["m1".to_owned(), "m2".to_owned()]),
+            textures: Some(vec!["t1".to_owned()]),
+            states: Some(states),
+        };
+
+        let mut loader = (Resource::<Model>::new(), Resource::<Texture>::new());
+
+        let furnace = super::convert(tile(vec![state(0, &[], "horizontal")]), &mut loader).unwrap();
+        assert_eq!(furnace.states.len(), 4);
+        assert_eq!(furnace.state_facing(Left).unwrap().shell.local_left(), Front);
+        assert_eq!(furnace.state_facing(Back).unwrap().shell.local_back(), Front);
+        assert!(furnace.state_facing(Up).is_none());
+
+        let fixed = super::convert(tile(vec![State { rotations: None, ..state(0, &[], "") }]), &mut loader).unwrap();
+        assert_eq!(fixed.state_facing(Front), Some(&fixed.states[0]));
+        assert!(fixed.state_facing(Back).is_none());
+
+        let log = super::convert(tile(vec![state(0, &[], "axis")]), &mut loader).unwrap();
+        assert_eq!(log.states.len(), 3);
+        assert_eq!(log.state_facing(Up).unwrap().shell, Shell::new());
+        assert!(log.state_facing(Down).is_none());
+        assert_eq!(log.state_along(Axis::Y).unwrap().shell, Shell::new());
+        assert_eq!(log.state_along(Axis::X).unwrap().shell.local_left(), Up);
+        assert_eq!(log.state_along(Axis::Z).unwrap().shell.local_front(), Up);
+
+        let all = super::convert(tile(vec![state(0, &[], "all")]), &mut loader).unwrap();
+        assert_eq!(all.states.len(), 24);
+
+        let states = vec![
+            state(0, &[], "horizontal"),
+            state(0, &["turn_y"], "horizontal"),
+            state(1, &[], "horizontal"),
+        ];
+        // States declared separately are never merged, even when their variants coincide
+        let tile = super::convert(tile(states), &mut loader).unwrap();
+        assert_eq!(tile.states.len(), 12);
+
+        let err = super::convert(Tile {
+            models: Some(vec!["m1".to_owned()]),
+            textures: Some(vec!["t1".to_owned()]),
+            states: Some(vec![state(0, &[], "diagonal")]),
+        }, &mut loader).unwrap_err();
+        assert_eq!(err, TileError::StateError(StateError::RotationsError));
+    }
+
+    #[test]
+    fn symmetric_rotations() {
+        use crate::engine::model::Model as Mesh;
+
+        let dir = std::env::temp_dir().join(format!("rt_tools_symmetry_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cube = "{ pos: [[0, 0, 0], [1, 0, 0], [1, 1, 0], [0, 1, 0], [0, 0, 1], [1, 0, 1], [1, 1, 1], [0, 1, 1]], \
+                    st: [[0, 0], [1, 0], [1, 1], [0, 1]], \
+                    faces: [ \
+                      { data: { pos: [4, 5, 6, 7], st: [0, 0, 0, 0] } }, { data: { pos: [1, 0, 3, 2], st: [0, 0, 0, 0] } }, \
+                      { data: { pos: [3, 7, 6, 2], st: [0, 0, 0, 0] } }, { data: { pos: [0, 1, 5, 4], st: [0, 0, 0, 0] } }, \
+                      { data: { pos: [5, 1, 2, 6], st: [0, 0, 0, 0] } }, { data: { pos: [0, 4, 7, 3], st: [0, 0, 0, 0] } } ] }";
+        let front = cube.replacen("st: [0, 0, 0, 0]", "st: [0, 1, 2, 3]", 1);
+
+        let plain = dir.join("plain.yml").to_string_lossy().into_owned();
+        let furnace = dir.join("furnace.yml").to_string_lossy().into_owned();
+        std::fs::write(&plain, cube).unwrap();
+        std::fs::write(&furnace, front).unwrap();
+
+        let tile = |model: &str, rotations: &str, count| Tile {
+            models: Some(vec![model.to_owned()]),
+            textures: Some(vec!["t1".to_owned()]),
+            states: Some((0..count)
+                .map(|_| State { model: Some(0), layers: Some(vec![0]), transform: None, rotations: Some(rotations.to_owned()) })
+                .collect()),
+        };
+
+        let mut loader = (Resource::<Mesh>::new(), Resource::<Texture>::new());
+        let convert = |tile, loader: &mut _| super::convert(tile, loader).unwrap().states;
+
+        // Spins around the facing axis look the same, one state per facing is left
+        let states = convert(tile(&plain, "all", 1), &mut loader);
+        assert_eq!(states.len(), 6);
+        assert!(Side::ALL.iter().all(|&side| states.iter().any(|s| s.facing == side)));
+
+        assert_eq!(convert(tile(&plain, "horizontal", 1), &mut loader).len(), 4);
+        assert_eq!(convert(tile(&plain, "all", 2), &mut loader).len(), 12);
+
+        // A textured front turns with the spin, so every orientation stays
+        assert_eq!(convert(tile(&furnace, "all", 1), &mut loader).len(), 24);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn place() {
+        use crate::engine::{orientation::Orientation, placement::Placement};
+        use glm::vec3;
+
+        let tile = |rotations: &str| {
+            let src = Tile {
+                models: Some(vec!["m1".to_owned()]),
+                textures: Some(vec!["t1".to_owned()]),
+                states: Some(vec![State {
+                    model: Some(0),
+                    layers: Some(vec![0]),
+                    transform: None,
+                    rotations: Some(rotations.to_owned()),
+                }]),
+            };
+
+            super::convert(src, &mut (Resource::<Model>::new(), Resource::<Texture>::new())).unwrap()
+        };
+
+        let look = vec3(0.3, -0.2, 1.);
+        let side_hit = Placement::new(Right, look, vec3(0., 2.8, 0.));
+        let top_hit = Placement::new(Up, look, vec3(0., 3., 0.));
+
+        let furnace = tile("horizontal");
+        let state = furnace.place(Rotations::Horizontal, &side_hit).unwrap();
+        assert_eq!(state.facing, Back);
+        assert_eq!(state.shell.local_back(), Front);
+
+        let log = tile("axis");
+        let state = log.place(Rotations::Axis, &side_hit).unwrap();
+        assert_eq!(state.shell.local_left(), Up);
+
+        let state = log.place(Rotations::Axis, &top_hit).unwrap();
+        assert_eq!(state.shell, Shell::new());
+
+        let stairs = tile("all");
+        let state = stairs.place(Rotations::All, &side_hit).unwrap();
+        assert_eq!(state.facing, Back);
+        assert_eq!(Orientation::from(state.shell).side(Up), Down);
 
-        assert_eq!(super::convert(tile, &mut loader).unwrap(), expected);
+        let state = stairs.place(Rotations::All, &top_hit).unwrap();
+        assert_eq!(state.facing, Back);
+        assert_eq!(Orientation::from(state.shell).side(Up), Up);
     }
 }
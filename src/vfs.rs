@@ -0,0 +1,289 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::asset::normalize;
+
+pub trait Vfs {
+    fn read(&self, file: &str) -> io::Result<Vec<u8>>;
+
+    fn exists(&self, file: &str) -> bool;
+
+    fn list(&self) -> io::Result<Vec<String>>;
+
+    fn read_to_string(&self, file: &str) -> io::Result<String> {
+        String::from_utf8(self.read(file)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn open_image(&self, file: &str) -> image::ImageResult<image::DynamicImage> {
+        let bytes = self.read(file).map_err(image::ImageError::IoError)?;
+        image::load_from_memory(&bytes)
+    }
+}
+
+impl<V: Vfs + ?Sized> Vfs for &V {
+    fn read(&self, file: &str) -> io::Result<Vec<u8>> { (**self).read(file) }
+
+    fn exists(&self, file: &str) -> bool { (**self).exists(file) }
+
+    fn list(&self) -> io::Result<Vec<String>> { (**self).list() }
+}
+
+impl<V: Vfs + ?Sized> Vfs for Box<V> {
+    fn read(&self, file: &str) -> io::Result<Vec<u8>> { (**self).read(file) }
+
+    fn exists(&self, file: &str) -> bool { (**self).exists(file) }
+
+    fn list(&self) -> io::Result<Vec<String>> { (**self).list() }
+}
+
+pub fn key(file: &str) -> io::Result<String> {
+    let path = normalize(file).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+    let parts: Vec<_> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+
+    Ok(parts.join("/"))
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DirVfs {
+    root: PathBuf,
+}
+
+impl DirVfs {
+    pub fn new<P>(root: P) -> Self
+        where
+            P: Into<PathBuf>,
+    { DirVfs { root: root.into() } }
+
+    pub fn root(&self) -> &Path { &self.root }
+
+    fn path(&self, file: &str) -> io::Result<PathBuf> { Ok(self.root.join(key(file)?)) }
+}
+
+impl Vfs for DirVfs {
+    fn read(&self, file: &str) -> io::Result<Vec<u8>> { fs::read(self.path(file)?) }
+
+    fn exists(&self, file: &str) -> bool { self.path(file).is_ok_and(|path| path.is_file()) }
+
+    fn list(&self) -> io::Result<Vec<String>> {
+        let mut files = BTreeMap::new();
+        collect_files(&self.root, &self.root, &mut files)?;
+        Ok(files.into_keys().collect())
+    }
+}
+
+const PAK_MAGIC: &[u8; 8] = b"RTPAK\0\0\x01";
+
+#[derive(Debug, Default)]
+pub struct PakVfs {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl PakVfs {
+    pub fn open<P>(path: P) -> io::Result<Self>
+        where
+            P: AsRef<Path>,
+    {
+        let file = fs::File::open(path)?;
+        let size = file.metadata()?.len();
+        Self::read_archive(io::BufReader::new(file), size)
+    }
+
+    pub fn from_reader<R>(reader: R) -> io::Result<Self>
+        where
+            R: Read,
+    { Self::read_archive(reader, u64::MAX) }
+
+    // `size` bounds every length read from the header, so a corrupt archive can't request
+    // more memory than the file holds
+    fn read_archive<R>(mut reader: R, size: u64) -> io::Result<Self>
+        where
+            R: Read,
+    {
+        let mut remaining = size;
+
+        let magic = read_chunk(&mut reader, PAK_MAGIC.len() as u64, &mut remaining)?;
+
+        if magic != PAK_MAGIC {
+            return Err(invalid("not a pak archive"));
+        }
+
+        let count = read_u64(&mut reader, &mut remaining)?;
+        let mut files = BTreeMap::new();
+
+        for _ in 0..count {
+            let name_len = read_u64(&mut reader, &mut remaining)?;
+            let name = read_chunk(&mut reader, name_len, &mut remaining)?;
+            let name = String::from_utf8(name).map_err(|_| invalid("invalid file name"))?;
+            let name = key(&name).map_err(|_| invalid("invalid file name"))?;
+
+            let data_len = read_u64(&mut reader, &mut remaining)?;
+            let data = read_chunk(&mut reader, data_len, &mut remaining)?;
+
+            files.insert(name, data);
+        }
+
+        Ok(PakVfs { files })
+    }
+
+    pub fn insert<S>(&mut self, file: S, data: Vec<u8>) -> io::Result<()>
+        where
+            S: AsRef<str>,
+    {
+        self.files.insert(key(file.as_ref())?, data);
+        Ok(())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item=&str> { self.files.keys().map(|k| k.as_str()) }
+}
+
+impl Vfs for PakVfs {
+    fn read(&self, file: &str) -> io::Result<Vec<u8>> {
+        self.files
+            .get(&key(file)?)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, file.to_owned()))
+    }
+
+    fn exists(&self, file: &str) -> bool { key(file).is_ok_and(|k| self.files.contains_key(&k)) }
+
+    fn list(&self) -> io::Result<Vec<String>> { Ok(self.files.keys().cloned().collect()) }
+}
+
+fn invalid(msg: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, msg.to_owned()) }
+
+fn read_u64<R: Read>(reader: &mut R, remaining: &mut u64) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&read_chunk(reader, 8, remaining)?);
+    Ok(u64::from_le_bytes(buf))
+}
+
+// Grows the buffer as data arrives instead of trusting `len` up front
+fn read_chunk<R: Read>(reader: &mut R, len: u64, remaining: &mut u64) -> io::Result<Vec<u8>> {
+    if len > *remaining {
+        return Err(invalid("entry exceeds archive size"));
+    }
+
+    let mut buf = Vec::new();
+    reader.take(len).read_to_end(&mut buf)?;
+
+    if buf.len() as u64 != len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated pak archive"));
+    }
+
+    *remaining -= len;
+    Ok(buf)
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut BTreeMap<String, PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let relative = path
+                .strip_prefix(root)
+                .map_err(io::Error::other)?;
+
+            let name: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+
+            files.insert(name.join("/"), path);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn pack<P, W>(dir: P, mut writer: W) -> io::Result<usize>
+    where
+        P: AsRef<Path>,
+        W: Write,
+{
+    let dir = dir.as_ref();
+    let mut files = BTreeMap::new();
+    collect_files(dir, dir, &mut files)?;
+
+    writer.write_all(PAK_MAGIC)?;
+    writer.write_all(&(files.len() as u64).to_le_bytes())?;
+
+    for (name, path) in &files {
+        let data = fs::read(path)?;
+
+        writer.write_all(&(name.len() as u64).to_le_bytes())?;
+        writer.write_all(name.as_bytes())?;
+        writer.write_all(&(data.len() as u64).to_le_bytes())?;
+        writer.write_all(&data)?;
+    }
+
+    writer.flush()?;
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_and_pak() {
+        let dir = std::env::temp_dir().join(format!("rt_tools_vfs_{}", std::process::id()));
+        let assets = dir.join("assets");
+        fs::create_dir_all(assets.join("models")).unwrap();
+        fs::create_dir_all(assets.join("tiles").join("nested")).unwrap();
+
+        fs::write(assets.join("models").join("cube.yml"), "faces: []").unwrap();
+        fs::write(assets.join("tiles").join("nested").join("stone.yml"), "states: []").unwrap();
+
+        let dir_vfs = DirVfs::new(&assets);
+        assert!(dir_vfs.exists("models/cube.yml"));
+        assert!(!dir_vfs.exists("models/sphere.yml"));
+        assert_eq!(dir_vfs.read_to_string("./models\\cube.yml").unwrap(), "faces: []");
+        assert_eq!(dir_vfs.list().unwrap(), ["models/cube.yml", "tiles/nested/stone.yml"]);
+
+        let mut archive = Vec::new();
+        assert_eq!(pack(&assets, &mut archive).unwrap(), 2);
+
+        let pak = PakVfs::from_reader(&*archive).unwrap();
+        assert_eq!(pak.files().collect::<Vec<_>>(), ["models/cube.yml", "tiles/nested/stone.yml"]);
+        assert_eq!(pak.read_to_string("tiles/nested/stone.yml").unwrap(), "states: []");
+        assert_eq!(pak.read("models/sphere.yml").unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        assert!(PakVfs::from_reader(&b"not a pak"[..]).is_err());
+
+        let archive_path = dir.join("assets.pak");
+        fs::write(&archive_path, &archive).unwrap();
+        assert_eq!(PakVfs::open(&archive_path).unwrap().list().unwrap(), pak.list().unwrap());
+
+        let mut huge = PAK_MAGIC.to_vec();
+        huge.extend_from_slice(&1u64.to_le_bytes());
+        huge.extend_from_slice(&(1u64 << 40).to_le_bytes());
+        assert_eq!(PakVfs::from_reader(&*huge).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        fs::write(&archive_path, &huge).unwrap();
+        assert_eq!(PakVfs::open(&archive_path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let truncated = &archive[..archive.len() - 4];
+        assert_eq!(PakVfs::from_reader(truncated).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        for file in &["../assets.pak", "/etc/passwd", "models/../../assets.pak"] {
+            assert!(!dir_vfs.exists(file));
+            assert_eq!(dir_vfs.read(file).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(pak.read(file).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        }
+
+        assert!(PakVfs::default().insert("../escape", vec![]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}